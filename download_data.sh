@@ -16,6 +16,8 @@ for u in `cat brightkite_topUsers.txt`
 do
   grep -P '^'$u'\t' loc-brightkite_totalCheckins.txt > full_bk$u.txt
   cut -f 3 full_bk$u.txt > bk$u.txt
+  # latitude and longitude for simulations in the plane
+  cut -f 3,4 full_bk$u.txt > plane_bk$u.txt
 done
 
 # remove temporary files
@@ -1,4 +1,9 @@
+use crate::randomized::{RandAlgorithm, RandCombine};
+use rand::Rng;
 use serverlib::prelude::*;
+use serverlib::Error;
+use std::cell::RefCell;
+use std::collections::HashMap;
 
 macro_rules! min {
     ($x: expr) => ($x);
@@ -12,20 +17,24 @@ macro_rules! min {
     }}
 }
 
-pub fn learning_augmented_alg<A: PredAlgorithm>(
+pub fn learning_augmented_alg<M: Metric, A: PredAlgorithm<M>>(
     alg: A,
-    instance: &Instance,
+    instance: &Instance<M>,
     prediction: &Prediction,
-) -> (Schedule, f64) {
+) -> (Schedule<M::Point>, f64) {
     alg.run(instance, prediction)
 }
 
-pub fn deterministic_alg<A: DetAlgorithm>(alg: A, instance: &Instance) -> (Schedule, f64) {
+pub fn deterministic_alg<M: Metric, A: DetAlgorithm<M>>(
+    alg: A,
+    instance: &Instance<M>,
+) -> (Schedule<M::Point>, f64) {
     alg.run_det(instance)
 }
 
-pub trait DetAlgorithm {
-    fn run_det(&self, instance: &Instance) -> (Schedule, f64) {
+/// A deterministic online algorithm for the k-server problem in the metric space `M`.
+pub trait DetAlgorithm<M: Metric = Line> {
+    fn run_det(&self, instance: &Instance<M>) -> (Schedule<M::Point>, f64) {
         let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
         let mut costs: f64 = 0.0;
 
@@ -41,19 +50,47 @@ pub trait DetAlgorithm {
         (schedule, costs)
     }
 
+    /// Like `run_det`, but fails instead of panicking if a request cannot be served.
+    fn try_run_det(&self, instance: &Instance<M>) -> Result<(Schedule<M::Point>, f64), Error> {
+        self.reset();
+        let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
+        let mut costs: f64 = 0.0;
+
+        for &req in instance.requests() {
+            let current = schedule.last().ok_or(Error::EmptySchedule)?;
+            let (mut next, cost) = self.try_next_det_move(current, req)?;
+            costs += cost;
+            next.normalize();
+            schedule.append_config(next);
+        }
+        Ok((schedule, costs))
+    }
+
     fn next_det_move(
         &self,
-        current: &ServerConfiguration,
-        next_request: Request,
-    ) -> (ServerConfiguration, f64);
+        current: &ServerConfiguration<M::Point>,
+        next_request: Request<M::Point>,
+    ) -> (ServerConfiguration<M::Point>, f64);
+
+    fn try_next_det_move(
+        &self,
+        current: &ServerConfiguration<M::Point>,
+        next_request: Request<M::Point>,
+    ) -> Result<(ServerConfiguration<M::Point>, f64), Error> {
+        Ok(self.next_det_move(current, next_request))
+    }
+
+    /// Forgets the state of previous runs, before the algorithm is driven move by move on a new instance.
+    fn reset(&self) {}
 }
 
-pub trait PredAlgorithm {
-    fn run(&self, instance: &Instance, pred: &Prediction) -> (Schedule, f64) {
+/// A learning-augmented online algorithm for the k-server problem in the metric space `M`.
+pub trait PredAlgorithm<M: Metric = Line> {
+    fn run(&self, instance: &Instance<M>, pred: &Prediction) -> (Schedule<M::Point>, f64) {
         let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
         let mut costs: f64 = 0.0;
 
-        for (&req, &pred) in instance.requests().into_iter().zip(pred.into_iter()) {
+        for (&req, &pred) in instance.requests().iter().zip(pred) {
             let current = schedule.last().unwrap();
             let (mut next, cost) = self.next_move(current, req, pred);
             costs += cost;
@@ -65,36 +102,91 @@ pub trait PredAlgorithm {
         (schedule, costs)
     }
 
+    /// Like `run`, but fails instead of panicking if a request cannot be served.
+    fn try_run(
+        &self,
+        instance: &Instance<M>,
+        pred: &Prediction,
+    ) -> Result<(Schedule<M::Point>, f64), Error> {
+        self.reset();
+        let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
+        let mut costs: f64 = 0.0;
+
+        for (&req, &pred) in instance.requests().iter().zip(pred) {
+            let current = schedule.last().ok_or(Error::EmptySchedule)?;
+            let (mut next, cost) = self.try_next_move(current, req, pred)?;
+            costs += cost;
+            next.normalize();
+            schedule.append_config(next);
+        }
+        Ok((schedule, costs))
+    }
+
     fn next_move(
         &self,
-        current: &ServerConfiguration,
-        next_request: Request,
+        current: &ServerConfiguration<M::Point>,
+        next_request: Request<M::Point>,
         prediction: usize,
-    ) -> (ServerConfiguration, f64);
+    ) -> (ServerConfiguration<M::Point>, f64);
+
+    fn try_next_move(
+        &self,
+        current: &ServerConfiguration<M::Point>,
+        next_request: Request<M::Point>,
+        prediction: usize,
+    ) -> Result<(ServerConfiguration<M::Point>, f64), Error> {
+        Ok(self.next_move(current, next_request, prediction))
+    }
+
+    /// Forgets the state of previous runs, before the algorithm is driven move by move on a new instance.
+    fn reset(&self) {}
 }
 
-impl<T: DetAlgorithm> PredAlgorithm for T {
+impl<M: Metric, T: DetAlgorithm<M>> PredAlgorithm<M> for T {
     fn next_move(
         &self,
-        current: &ServerConfiguration,
-        next_request: Request,
+        current: &ServerConfiguration<M::Point>,
+        next_request: Request<M::Point>,
         _: usize,
-    ) -> (ServerConfiguration, f64) {
+    ) -> (ServerConfiguration<M::Point>, f64) {
         self.next_det_move(current, next_request)
     }
+
+    fn try_next_move(
+        &self,
+        current: &ServerConfiguration<M::Point>,
+        next_request: Request<M::Point>,
+        _: usize,
+    ) -> Result<(ServerConfiguration<M::Point>, f64), Error> {
+        self.try_next_det_move(current, next_request)
+    }
+
+    fn reset(&self) {
+        DetAlgorithm::reset(self)
+    }
 }
 
 pub struct DoubleCoverage;
 
 impl DetAlgorithm for DoubleCoverage {
+    /// Panics if the configuration has no servers, see `try_next_det_move`.
     fn next_det_move(
         &self,
         current: &ServerConfiguration,
         req: Request,
     ) -> (ServerConfiguration, f64) {
-        let (left, right) = current.adjacent_servers(&req);
+        self.try_next_det_move(current, req)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_next_det_move(
+        &self,
+        current: &ServerConfiguration,
+        req: Request,
+    ) -> Result<(ServerConfiguration, f64), Error> {
+        let (left, right) = current.try_adjacent_servers(&req)?;
         let mut res = current.clone();
-        let pos = req.distance_from(&0.0);
+        let pos = *req.pos();
         match (left, right) {
             (Some(i), Some(j)) => {
                 let d = min!(
@@ -107,10 +199,10 @@ impl DetAlgorithm for DoubleCoverage {
             (Some(i), None) | (None, Some(i)) => {
                 res[i] = pos;
             }
-            _ => panic!("Should not happened!"),
+            (None, None) => return Err(Error::NoServers),
         }
         let costs = current.diff(&res);
-        return (res, costs);
+        Ok((res, costs))
     }
 }
 
@@ -136,17 +228,28 @@ impl LambdaDC {
 }
 
 impl PredAlgorithm for LambdaDC {
+    /// Panics if the configuration has no servers, see `try_next_move`.
     fn next_move(
         &self,
         current: &ServerConfiguration,
         req: Request,
         predicted: usize,
     ) -> (ServerConfiguration, f64) {
+        self.try_next_move(current, req, predicted)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_next_move(
+        &self,
+        current: &ServerConfiguration,
+        req: Request,
+        predicted: usize,
+    ) -> Result<(ServerConfiguration, f64), Error> {
         let pos = match req {
             Request::Simple(x) => x,
             Request::Relocation(x, _) => x,
         };
-        let (left, right) = current.adjacent_servers(&req);
+        let (left, right) = current.try_adjacent_servers(&req)?;
         let mut res = current.clone();
         match (left, right) {
             (Some(i), Some(j)) => {
@@ -191,13 +294,14 @@ impl PredAlgorithm for LambdaDC {
             (Some(i), None) | (None, Some(i)) => {
                 res[i] = pos;
             }
-            _ => panic!("Should not happen!"),
+            (None, None) => return Err(Error::NoServers),
         }
         let costs = current.diff(&res);
-        return (res, costs);
+        Ok((res, costs))
     }
 }
 
+/// Combines follow-the-prediction and `DoubleCoverage`, see `Combiner`.
 pub struct CombineDet {
     gamma: f64,
 }
@@ -208,49 +312,116 @@ impl CombineDet {
     }
 }
 
+impl CombineDet {
+    fn combiner(&self) -> Combiner {
+        let ftp = LambdaDC::new(0.0);
+        Combiner::new(vec![Box::new(ftp), Box::new(DoubleCoverage)], self.gamma)
+    }
+}
+
 impl PredAlgorithm for CombineDet {
     fn run(&self, instance: &Instance, prediction: &Prediction) -> (Schedule, f64) {
-        // Setup variables
-        let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
-        let mut dc_schedule = Schedule::with_initial_config(instance.initial_positions().clone());
-        let mut ftp_schedule = Schedule::with_initial_config(instance.initial_positions().clone());
-        let mut dc_costs: f64 = 0.0;
-        let mut ftp_costs: f64 = 0.0;
-        let mut current_dc = false;
+        self.combiner().run(instance, prediction)
+    }
+
+    fn try_run(
+        &self,
+        instance: &Instance,
+        prediction: &Prediction,
+    ) -> Result<(Schedule, f64), Error> {
+        self.combiner().try_run(instance, prediction)
+    }
+
+    fn next_move(
+        &self,
+        current: &ServerConfiguration,
+        _next_request: Request,
+        _request_index: usize,
+    ) -> (ServerConfiguration, f64) {
+        (current.clone(), 0.0)
+    }
+}
+
+/// The names of the algorithms accepted by `algorithm_from_name`.
+pub const ALGORITHM_NAMES: [&str; 5] = ["ftp", "dc", "ldc", "wfa", "greedy"];
+
+/// Creates the algorithm with the given name, where `lambda` is the parameter of `LambdaDC`.
+pub fn algorithm_from_name(name: &str, lambda: f32) -> Result<Box<dyn PredAlgorithm>, Error> {
+    match name {
+        "ftp" => Ok(Box::new(LambdaDC::new(0.0))),
+        "dc" => Ok(Box::new(DoubleCoverage)),
+        "ldc" => Ok(Box::new(LambdaDC::new(lambda))),
+        "wfa" => Ok(Box::new(WorkFunction::new())),
+        "greedy" => Ok(Box::new(Greedy::new(Line))),
+        _ => Err(Error::UnknownAlgorithm(name.to_string())),
+    }
+}
+
+/// Simulates several algorithms in parallel and follows one of them at a time.
+///
+/// The algorithms are followed in turn: as soon as the cost of the followed algorithm exceeds the
+/// current bound, the next one is followed and the bound is multiplied by `1 + gamma`. The cost of
+/// the combination includes moving the servers to the configuration of the next algorithm.
+pub struct Combiner {
+    algorithms: Vec<Box<dyn PredAlgorithm>>,
+    gamma: f64,
+}
+
+impl Combiner {
+    pub fn new(algorithms: Vec<Box<dyn PredAlgorithm>>, gamma: f64) -> Combiner {
+        assert!(
+            !algorithms.is_empty(),
+            "There must be an algorithm to combine"
+        );
+        assert!(gamma > 0.0, "gamma must be positive");
+        Combiner { algorithms, gamma }
+    }
+}
+
+impl PredAlgorithm for Combiner {
+    /// Panics if an algorithm cannot serve a request, see `try_run`.
+    fn run(&self, instance: &Instance, prediction: &Prediction) -> (Schedule, f64) {
+        self.try_run(instance, prediction)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_run(
+        &self,
+        instance: &Instance,
+        prediction: &Prediction,
+    ) -> Result<(Schedule, f64), Error> {
+        let initial = instance.initial_positions();
+        let mut schedule = Schedule::with_initial_config(initial.clone());
+        let mut configs = vec![initial.clone(); self.algorithms.len()];
+        let mut costs = vec![0.0; self.algorithms.len()];
+        let mut current = 0;
         let mut bound = 1.0;
+        for alg in &self.algorithms {
+            alg.reset();
+        }
 
-        let dc = DoubleCoverage;
+        for (&req, &pred) in instance.requests().iter().zip(prediction) {
+            // Simulate all algorithms
+            for (i, alg) in self.algorithms.iter().enumerate() {
+                let (mut next, cost) = alg.try_next_move(&configs[i], req, pred)?;
+                next.normalize();
+                costs[i] += cost;
+                configs[i] = next;
+            }
 
-        // For each request and prediction
-        for (&req, &pred) in instance.requests().into_iter().zip(prediction.into_iter()) {
-            // Simulate follow-the-prediction
-            let ftp = LambdaDC::new(0.0);
-            let (next, cost) = ftp.next_move(ftp_schedule.last().unwrap(), req, pred);
-            ftp_costs += cost;
-            ftp_schedule.append_config(next);
-
-            // Simulate Double-Coverage
-            let (next, cost) = dc.next_move(dc_schedule.last().unwrap(), req, pred);
-            dc_costs += cost;
-            dc_schedule.append_config(next);
-
-            // Select the better algorithm
-            while (current_dc && dc_costs > bound) || (!current_dc && ftp_costs > bound) {
-                current_dc = !current_dc;
+            // Select the next algorithm until the followed one is within the bound
+            while costs[current] > bound {
+                current = (current + 1) % self.algorithms.len();
                 bound *= 1.0 + self.gamma;
             }
 
             // Append the latest configuration of the selected algorithm to the overall schedule
-            if current_dc {
-                schedule.append_config(dc_schedule.last().unwrap().clone());
-            } else {
-                schedule.append_config(ftp_schedule.last().unwrap().clone());
-            }
+            schedule.append_config(configs[current].clone());
         }
 
-        // Compute the cost for the final schedule
+        // Compute the cost for the final schedule, which includes switching between configurations
         let costs = schedule.cost();
-        (schedule, costs)
+        Ok((schedule, costs))
     }
 
     fn next_move(
@@ -263,6 +434,338 @@ impl PredAlgorithm for CombineDet {
     }
 }
 
+/// The randomized counterpart of `CombineDet`: combines follow-the-prediction and `DoubleCoverage` by
+/// multiplicative weights with the learning rate `beta`, see `RandCombine::robust_ftp`.
+///
+/// In contrast to `RandCombine`, it does not depend on lambda.
+#[derive(Debug, Clone)]
+pub struct CombineRand(RandCombine);
+
+impl CombineRand {
+    pub fn new(beta: f64) -> CombineRand {
+        CombineRand(RandCombine::robust_ftp(beta))
+    }
+}
+
+impl RandAlgorithm for CombineRand {
+    fn run_rand<R: Rng>(
+        &self,
+        instance: &Instance,
+        prediction: &Prediction,
+        rng: &mut R,
+    ) -> (Schedule, f64) {
+        self.0.run_rand(instance, prediction, rng)
+    }
+
+    fn next_rand_move<R: Rng>(
+        &self,
+        current: &ServerConfiguration,
+        next_request: Request,
+        prediction: usize,
+        rng: &mut R,
+    ) -> (ServerConfiguration, f64) {
+        self.0
+            .next_rand_move(current, next_request, prediction, rng)
+    }
+}
+
+/// Serves each request with the closest server. Works in any metric space.
+#[derive(Debug, Clone)]
+pub struct Greedy<M: Metric> {
+    metric: M,
+}
+
+impl<M: Metric> Greedy<M> {
+    pub fn new(metric: M) -> Greedy<M> {
+        Greedy { metric }
+    }
+}
+
+impl<M: Metric> DetAlgorithm<M> for Greedy<M> {
+    fn next_det_move(
+        &self,
+        current: &ServerConfiguration<M::Point>,
+        req: Request<M::Point>,
+    ) -> (ServerConfiguration<M::Point>, f64) {
+        let distances: Vec<f32> = current
+            .into_iter()
+            .map(|server| self.metric.distance(server, req.pos()))
+            .collect();
+        let closest = (0..current.size())
+            .min_by(|&a, &b| distances[a].partial_cmp(&distances[b]).unwrap())
+            .unwrap();
+        let mut res = current.clone();
+        res[closest] = *req.pos();
+        (res, distances[closest] as f64)
+    }
+}
+
+/// Serves each request with the predicted server. Works in any metric space.
+#[derive(Debug, Clone)]
+pub struct FollowPrediction<M: Metric> {
+    metric: M,
+}
+
+impl<M: Metric> FollowPrediction<M> {
+    pub fn new(metric: M) -> FollowPrediction<M> {
+        FollowPrediction { metric }
+    }
+
+    fn follow(
+        &self,
+        current: &ServerConfiguration<M::Point>,
+        req: Request<M::Point>,
+        predicted: usize,
+    ) -> (ServerConfiguration<M::Point>, f64) {
+        let mut res = current.clone();
+        res[predicted] = *req.pos();
+        let costs = self.metric.distance(&current[predicted], req.pos()) as f64;
+        (res, costs)
+    }
+}
+
+// A blanket implementation for all metrics would overlap with the one for deterministic algorithms.
+macro_rules! impl_follow_prediction {
+    ($($metric: ty),*) => {$(
+        impl PredAlgorithm<$metric> for FollowPrediction<$metric> {
+            fn next_move(
+                &self,
+                current: &ServerConfiguration<<$metric as Metric>::Point>,
+                req: Request<<$metric as Metric>::Point>,
+                predicted: usize,
+            ) -> (ServerConfiguration<<$metric as Metric>::Point>, f64) {
+                self.follow(current, req, predicted)
+            }
+        }
+    )*};
+}
+
+impl_follow_prediction!(Line, Circle, Tree, Plane);
+
+/// The Work Function Algorithm for the k-server problem on the line.
+///
+/// The work function `w(X)` is the optimal cost of serving all requests seen so far and ending in
+/// configuration `X`. On the line, it suffices to know `w` for configurations whose servers are
+/// located at initial positions or previously requested points. When a new point is requested, the
+/// work function is extended to configurations using this point, since any server moving onto it
+/// passes one of its neighbouring points. The work function is tracked for all `k`-multisets of these
+/// points, i.e., `Θ(points^k)` configurations, so it is only tractable for small `k` and short
+/// instances, see `WorkFunction::is_tractable`.
+#[derive(Default)]
+pub struct WorkFunction {
+    state: RefCell<Option<WorkFunctionState>>,
+}
+
+impl WorkFunction {
+    /// The maximum number of configurations whose work function is tracked.
+    pub const MAX_STATES: f64 = 1e6;
+
+    pub fn new() -> WorkFunction {
+        WorkFunction {
+            state: RefCell::new(None),
+        }
+    }
+
+    /// Whether the work function of an instance with `k` servers and `length` requests is tracked for
+    /// at most `MAX_STATES` configurations, i.e., `k`-multisets of up to `k + length` points.
+    pub fn is_tractable(k: usize, length: usize) -> bool {
+        let points = k + length;
+        let states = (0..k).fold(1.0, |acc, i| acc * (points + i) as f64 / (i + 1) as f64);
+        states <= Self::MAX_STATES
+    }
+}
+
+impl DetAlgorithm for WorkFunction {
+    fn run_det(&self, instance: &Instance) -> (Schedule, f64) {
+        self.state
+            .replace(Some(WorkFunctionState::new(instance.initial_positions())));
+        let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
+        let mut costs: f64 = 0.0;
+
+        for &req in instance.requests() {
+            let current = schedule.last().unwrap();
+            let (next, cost) = self.next_det_move(current, req);
+            costs += cost;
+            schedule.append_config(next);
+        }
+        (schedule, costs)
+    }
+
+    fn next_det_move(
+        &self,
+        current: &ServerConfiguration,
+        req: Request,
+    ) -> (ServerConfiguration, f64) {
+        let mut state = self.state.borrow_mut();
+        // A work function of another run, which ended in a different configuration, is discarded
+        if state.as_ref().is_none_or(|state| state.config != *current) {
+            *state = Some(WorkFunctionState::new(current));
+        }
+        let state = state.as_mut().unwrap();
+        let pos = *req.pos();
+        state.serve(pos);
+
+        let positions = current.into_iter().copied().collect::<Vec<f32>>();
+        if positions.contains(&pos) {
+            return (current.clone(), 0.0);
+        }
+        let mut best: Option<(usize, f64)> = None;
+        for (i, &server) in positions.iter().enumerate() {
+            let value = state.value(&replace(&positions, i, pos)) + (server - pos).abs() as f64;
+            if best.is_none_or(|(_, v)| value < v) {
+                best = Some((i, value));
+            }
+        }
+        let (server, _) = best.unwrap();
+        let res = current.from_move(server, pos);
+        let costs = current.diff(&res);
+        state.config = res.clone();
+        (res, costs)
+    }
+
+    fn reset(&self) {
+        self.state.replace(None);
+    }
+}
+
+/// The work function restricted to all configurations on the currently known points.
+struct WorkFunctionState {
+    /// The configuration the algorithm is currently in.
+    config: ServerConfiguration,
+    k: usize,
+    points: Vec<f32>,
+    values: HashMap<Vec<u32>, f64>,
+}
+
+impl WorkFunctionState {
+    fn new(initial: &ServerConfiguration) -> WorkFunctionState {
+        let initial = initial.into_iter().copied().collect::<Vec<f32>>();
+        let mut points = initial.clone();
+        points.dedup();
+        let values = multisets(&points, initial.len())
+            .into_iter()
+            .map(|config| {
+                let cost = config
+                    .iter()
+                    .zip(initial.iter())
+                    .map(|(a, b)| (a - b).abs() as f64)
+                    .sum();
+                (key(&config), cost)
+            })
+            .collect();
+        WorkFunctionState {
+            config: ServerConfiguration::from(initial.clone()),
+            k: initial.len(),
+            points,
+            values,
+        }
+    }
+
+    fn value(&self, config: &[f32]) -> f64 {
+        self.values[&key(config)]
+    }
+
+    /// Extends the work function to all configurations with at least one server at `point`.
+    fn add_point(&mut self, point: f32) {
+        let idx = self.points.partition_point(|&x| x < point);
+        let left = if idx > 0 {
+            Some(self.points[idx - 1])
+        } else {
+            None
+        };
+        let right = self.points.get(idx).copied();
+
+        let mut new_values = vec![];
+        for m in 1..=self.k {
+            for rest in multisets(&self.points, self.k - m) {
+                // j servers enter `point` from the left neighbour, the others from the right one.
+                let mut best = f64::INFINITY;
+                for j in 0..=m {
+                    let from_left = match left {
+                        Some(l) => vec![l; j],
+                        None if j == 0 => vec![],
+                        None => continue,
+                    };
+                    let from_right = match right {
+                        Some(r) => vec![r; m - j],
+                        None if j == m => vec![],
+                        None => continue,
+                    };
+                    let move_cost = from_left
+                        .iter()
+                        .chain(from_right.iter())
+                        .map(|x| (x - point).abs() as f64)
+                        .sum::<f64>();
+                    let mut config = rest.clone();
+                    config.extend(from_left);
+                    config.extend(from_right);
+                    config.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                    best = best.min(self.value(&config) + move_cost);
+                }
+                let mut config = rest;
+                config.extend(vec![point; m]);
+                config.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                new_values.push((key(&config), best));
+            }
+        }
+        self.values.extend(new_values);
+        self.points.insert(idx, point);
+    }
+
+    /// Updates the work function after serving a request at `pos`.
+    fn serve(&mut self, pos: f32) {
+        if self
+            .points
+            .binary_search_by(|x| x.partial_cmp(&pos).unwrap())
+            .is_err()
+        {
+            self.add_point(pos);
+        }
+        let values = self
+            .values
+            .keys()
+            .map(|k| {
+                let config = k.iter().map(|&b| f32::from_bits(b)).collect::<Vec<f32>>();
+                let value = config
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &x)| self.value(&replace(&config, i, pos)) + (x - pos).abs() as f64)
+                    .fold(f64::INFINITY, f64::min);
+                (k.clone(), value)
+            })
+            .collect();
+        self.values = values;
+    }
+}
+
+fn key(config: &[f32]) -> Vec<u32> {
+    // Adding 0.0 maps -0.0 to 0.0, so that both have the same key.
+    config.iter().map(|&x| (x + 0.0).to_bits()).collect()
+}
+
+/// Replaces the `i`-th position of a sorted configuration with `pos` and keeps it sorted.
+fn replace(config: &[f32], i: usize, pos: f32) -> Vec<f32> {
+    let mut res = config.to_vec();
+    res[i] = pos;
+    res.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    res
+}
+
+/// All sorted multisets of the given size over the sorted `points`.
+fn multisets(points: &[f32], size: usize) -> Vec<Vec<f32>> {
+    if size == 0 {
+        return vec![vec![]];
+    }
+    let mut res = vec![];
+    for (i, &p) in points.iter().enumerate() {
+        for mut rest in multisets(&points[i..], size - 1) {
+            rest.insert(0, p);
+            res.push(rest);
+        }
+    }
+    res
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -282,7 +785,16 @@ mod tests {
                 vec![50, 50]
             ]),
             dc.run_det(&instance).0
-        )
+        );
+        assert_eq!(Ok(dc.run_det(&instance)), dc.try_run_det(&instance));
+
+        let no_servers = Instance::from((vec![20, 80], Vec::<i32>::new()));
+        assert_eq!(Err(Error::NoServers), dc.try_run_det(&no_servers));
+
+        let negative = Instance::from((vec![-5], vec![0, 10]));
+        let (schedule, cost) = dc.run_det(&negative);
+        assert_eq!(Schedule::from(vec![vec![0, 10], vec![-5, 10]]), schedule);
+        assert_eq!(5.0, cost);
     }
     #[test]
     fn test_min() {
@@ -304,7 +816,10 @@ mod tests {
                 vec![43, 64],
             ]),
             alg.run(&instance, &pred).0
-        )
+        );
+
+        let no_servers = Instance::from((vec![20, 80], Vec::<i32>::new()));
+        assert_eq!(Err(Error::NoServers), alg.try_run(&no_servers, &pred));
     }
 
     #[test]
@@ -323,4 +838,160 @@ mod tests {
             alg.run(&instance, &pred).0
         )
     }
+
+    #[test]
+    fn test_work_function() {
+        let instance = Instance::from((vec![20, 80, 30, 70, 60, 50], vec![50, 50]));
+        let alg = WorkFunction::new();
+        assert_eq!(
+            Schedule::from(vec![
+                vec![50, 50],
+                vec![20, 50],
+                vec![20, 80],
+                vec![30, 80],
+                vec![30, 70],
+                vec![30, 60],
+                vec![30, 50]
+            ]),
+            alg.run_det(&instance).0
+        )
+    }
+
+    #[test]
+    fn test_work_function_follows_opt() {
+        let instance = Instance::from((vec![17, 17, 5, 14, 16, 17], vec![14, 14]));
+        let alg = WorkFunction::new();
+        let (schedule, cost) = alg.run_det(&instance);
+        assert_eq!(
+            Schedule::from(vec![
+                vec![14, 14],
+                vec![14, 17],
+                vec![14, 17],
+                vec![5, 17],
+                vec![5, 14],
+                vec![5, 16],
+                vec![5, 17],
+            ]),
+            schedule
+        );
+        assert_eq!(18.0, cost);
+    }
+
+    #[test]
+    fn test_work_function_resets_between_runs() {
+        let instance = Instance::from((vec![78, 77, 30, 8, 15, 58, 37, 19, 11, 7], vec![91, 91]));
+        let alg = WorkFunction::new();
+        let (_, first) = alg.run_det(&instance);
+        let (_, second) = alg.run_det(&instance);
+        assert_eq!(160.0, first);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_work_function_is_tractable() {
+        assert!(WorkFunction::is_tractable(2, 1000));
+        assert!(WorkFunction::is_tractable(3, 100));
+        assert!(!WorkFunction::is_tractable(3, 1000));
+        assert!(!WorkFunction::is_tractable(50, 1000));
+    }
+
+    #[test]
+    fn test_combiner_resets_work_function() {
+        let instance = Instance::from((vec![4, 12, 2], vec![1, 4]));
+        let other = Instance::from((vec![17, 17, 9], vec![1, 4]));
+        let pred = Prediction::from(vec![0, 0, 0]);
+        let alg = Combiner::new(vec![algorithm_from_name("wfa", 0.0).unwrap()], 1.0);
+        let (_, first) = alg.run(&instance, &pred);
+        // the work function of the other instance must not leak into the second run
+        alg.run(&other, &pred);
+        let (_, second) = alg.run(&instance, &pred);
+        assert_eq!(9.0, first);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_greedy_on_plane() {
+        let instance = Instance::with_metric(
+            Plane,
+            vec![(3, 4), (3, 0), (0, 1)]
+                .into_iter()
+                .map(|p| Request::Simple(PlanePoint::from(p)))
+                .collect(),
+            ServerConfiguration::new(vec![PlanePoint::from((0, 0)), PlanePoint::from((6, 0))]),
+        );
+        let (schedule, cost) = deterministic_alg(Greedy::new(Plane), &instance);
+        assert_eq!(
+            &ServerConfiguration::new(vec![PlanePoint::from((3, 4)), PlanePoint::from((0, 1))]),
+            schedule.last().unwrap()
+        );
+        // the tie for (3, 4) is broken by the index, then the second server serves both other requests
+        assert_eq!(5.0 + 3.0 + 10f32.sqrt() as f64, cost);
+        assert_eq!(cost, schedule.metric_cost(&Plane));
+    }
+
+    #[test]
+    fn test_follow_prediction() {
+        let instance = Instance::from((vec![20, 80, 40, 64], vec![50, 50]));
+        let pred = Prediction::from(vec![0, 1, 0, 1]);
+        let (schedule, cost) =
+            learning_augmented_alg(FollowPrediction::new(Line), &instance, &pred);
+        let (lambda_schedule, lambda_cost) =
+            learning_augmented_alg(LambdaDC::new(0.0), &instance, &pred);
+        assert_eq!(lambda_schedule, schedule);
+        assert_eq!(lambda_cost, cost);
+    }
+
+    #[test]
+    fn test_combiner() {
+        let instance = Instance::from((vec![20, 80, 40, 64, 10, 90], vec![50, 50]));
+        let pred = Prediction::from(vec![0, 1, 0, 1, 1, 0]);
+
+        // a single algorithm is always followed
+        let combiner = Combiner::new(vec![Box::new(DoubleCoverage)], 1.0);
+        assert_eq!(
+            DoubleCoverage.run(&instance, &pred),
+            combiner.run(&instance, &pred)
+        );
+
+        // switching to the second algorithm costs the distance between their configurations
+        let names = ["dc", "ftp"];
+        let algorithms = names
+            .iter()
+            .map(|name| algorithm_from_name(name, 0.5).unwrap())
+            .collect();
+        let (schedule, cost) = Combiner::new(algorithms, 1.0).run(&instance, &pred);
+        let (dc_schedule, _) = DoubleCoverage.run(&instance, &pred);
+        let (ftp_schedule, _) = LambdaDC::new(0.0).run(&instance, &pred);
+        assert_eq!(cost, schedule.cost());
+        assert_eq!(ftp_schedule.last(), schedule.last());
+        assert_eq!(dc_schedule.into_iter().nth(1), schedule.into_iter().nth(1));
+        assert_eq!(
+            Some(Error::UnknownAlgorithm("opt".to_string())),
+            algorithm_from_name("opt", 0.5).err()
+        );
+    }
+
+    #[test]
+    fn test_combine_rand() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let instance = Instance::from((vec![20, 80, 40, 64, 10, 90, 30, 70], vec![50, 50]));
+        let pred = Prediction::from(vec![0, 1, 0, 1, 1, 0, 0, 1]);
+        let alg = CombineRand::new(0.3);
+        for seed in 0..10 {
+            let (schedule, cost) = alg.run_rand(&instance, &pred, &mut StdRng::seed_from_u64(seed));
+            assert_eq!(cost, schedule.cost());
+            // follow-the-prediction is LambdaDC with lambda = 0
+            let expected = RandCombine::new(0.0, 0.3).run_rand(
+                &instance,
+                &pred,
+                &mut StdRng::seed_from_u64(seed),
+            );
+            assert_eq!(expected, (schedule.clone(), cost));
+            for (config, req) in schedule.into_iter().skip(1).zip(instance.requests()) {
+                assert!(config.into_iter().any(|s| s == *req.pos()));
+            }
+        }
+    }
 }
@@ -0,0 +1,179 @@
+use crate::algorithms::{DetAlgorithm, PredAlgorithm};
+use serverlib::prelude::*;
+
+/// Double Coverage on the circle: the two servers adjacent to the request move towards it at the same speed.
+#[derive(Debug, Clone)]
+pub struct DCCircle {
+    circle: Circle,
+}
+
+impl DCCircle {
+    pub fn new(circle: Circle) -> DCCircle {
+        DCCircle { circle }
+    }
+}
+
+impl DetAlgorithm<Circle> for DCCircle {
+    fn next_det_move(
+        &self,
+        current: &ServerConfiguration<CirclePoint>,
+        req: Request<CirclePoint>,
+    ) -> (ServerConfiguration<CirclePoint>, f64) {
+        serve(&self.circle, current, *req.pos(), 1.0, None)
+    }
+}
+
+/// The learning-augmented variant of `DCCircle`.
+///
+/// The adjacent server on the side from which the predicted server reaches the request on its
+/// shortest path moves at speed one, the other adjacent server at speed `lambda`.
+#[derive(Debug, Clone)]
+pub struct LambdaDCCircle {
+    circle: Circle,
+    lambda: f32,
+}
+
+impl LambdaDCCircle {
+    pub fn new(circle: Circle, lambda: f32) -> LambdaDCCircle {
+        LambdaDCCircle { circle, lambda }
+    }
+}
+
+impl PredAlgorithm<Circle> for LambdaDCCircle {
+    fn next_move(
+        &self,
+        current: &ServerConfiguration<CirclePoint>,
+        req: Request<CirclePoint>,
+        predicted: usize,
+    ) -> (ServerConfiguration<CirclePoint>, f64) {
+        serve(
+            &self.circle,
+            current,
+            *req.pos(),
+            self.lambda,
+            Some(predicted),
+        )
+    }
+}
+
+fn serve(
+    circle: &Circle,
+    current: &ServerConfiguration<CirclePoint>,
+    pos: CirclePoint,
+    lambda: f32,
+    predicted: Option<usize>,
+) -> (ServerConfiguration<CirclePoint>, f64) {
+    let (i, j) = circle.adjacent_servers(current, &Request::Simple(pos));
+    let mut res = current.clone();
+    if i == j {
+        // either a server is located at the request or there is only a single server
+        let costs = circle.distance(&current[i], &pos) as f64;
+        res[i] = pos;
+        return (res, costs);
+    }
+
+    // i moves clockwise, j moves counterclockwise
+    let dist_i = circle.clockwise(&current[i], &pos);
+    let dist_j = circle.clockwise(&pos, &current[j]);
+    let (move_i, move_j) = match predicted {
+        None => {
+            let d = dist_i.min(dist_j);
+            (d, d)
+        }
+        Some(p) if circle.clockwise(&current[p], &pos) <= circle.clockwise(&pos, &current[p]) => {
+            get_distances(dist_i, dist_j, lambda)
+        }
+        Some(_) => {
+            let (fast, slow) = get_distances(dist_j, dist_i, lambda);
+            (slow, fast)
+        }
+    };
+    // Fix rounding errors
+    res[i] = if move_i >= dist_i {
+        pos
+    } else {
+        circle.move_by(&current[i], move_i)
+    };
+    res[j] = if move_j >= dist_j {
+        pos
+    } else {
+        circle.move_by(&current[j], -move_j)
+    };
+    (res, (move_i + move_j) as f64)
+}
+
+/// The distances travelled by the fast and the slow server until one of them reaches the request.
+fn get_distances(fast: f32, slow: f32, lambda: f32) -> (f32, f32) {
+    if slow > lambda * fast {
+        (fast, lambda * fast)
+    } else {
+        (slow / lambda, slow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::*;
+
+    fn get_circle() -> Circle {
+        Circle::new(100.0)
+    }
+
+    fn get_instance(requests: Vec<f32>, initial: Vec<f32>) -> Instance<Circle> {
+        let circle = get_circle();
+        Instance::with_metric(
+            circle,
+            requests
+                .into_iter()
+                .map(|r| Request::Simple(circle.point(r)))
+                .collect(),
+            get_config(initial),
+        )
+    }
+
+    fn get_config(positions: Vec<f32>) -> ServerConfiguration<CirclePoint> {
+        let circle = get_circle();
+        ServerConfiguration::new(positions.into_iter().map(|x| circle.point(x)).collect())
+    }
+
+    #[test]
+    fn test_dc_circle() {
+        // the first and second request are served across 0
+        let instance = get_instance(vec![95.0, 50.0, 80.0], vec![10.0, 60.0]);
+        let (schedule, cost) = deterministic_alg(DCCircle::new(get_circle()), &instance);
+        assert_eq!(
+            Schedule::from(vec![
+                get_config(vec![10.0, 60.0]),
+                get_config(vec![95.0, 75.0]),
+                get_config(vec![20.0, 50.0]),
+                get_config(vec![90.0, 80.0]),
+            ]),
+            schedule
+        );
+        assert_eq!(140.0, cost);
+        assert_eq!(cost, schedule.metric_cost(instance.metric()));
+    }
+
+    #[test]
+    fn test_lambda_dc_circle() {
+        let instance = get_instance(vec![95.0, 20.0], vec![10.0, 60.0]);
+        // the server at 10 reaches 95 counterclockwise, the server at 95 reaches 20 clockwise
+        let pred = Prediction::from(vec![0, 0]);
+        let (schedule, cost) =
+            learning_augmented_alg(LambdaDCCircle::new(get_circle(), 0.5), &instance, &pred);
+        assert_eq!(
+            Schedule::from(vec![
+                get_config(vec![10.0, 60.0]),
+                get_config(vec![95.0, 67.5]),
+                get_config(vec![20.0, 55.0]),
+            ]),
+            schedule
+        );
+        assert_eq!(22.5 + 37.5, cost);
+
+        let (_, cost) =
+            learning_augmented_alg(LambdaDCCircle::new(get_circle(), 0.0), &instance, &pred);
+        assert_eq!(15.0 + 25.0, cost);
+    }
+}
@@ -1,53 +1,194 @@
 pub mod algorithms;
+pub mod circle;
+pub mod randomized;
+pub mod tree;
 
 use samplelib::*;
+use serverlib::prelude::{Circle, Line, Plane, Schedule, Tree};
+use serverlib::Error;
 use crate::algorithms::*;
+use crate::circle::*;
+use crate::randomized::*;
+use crate::tree::*;
+use rand::Rng;
 
 
-pub fn simulate_kserver(sample: &Sample, gamma: f64, lambda: f32, lazy: bool) -> Vec<SimResult> {
+/// Simulates the k-server algorithms on the line.
+///
+/// In `debug` mode, the schedules of the deterministic algorithms are validated against the instance.
+/// The randomized algorithms draw their choices from `rng`.
+#[allow(clippy::too_many_arguments)]
+pub fn simulate_kserver<R: Rng>(
+    sample: &Sample,
+    gamma: f64,
+    lambda: f32,
+    lazy: bool,
+    wfa: bool,
+    runs: usize,
+    beta: f64,
+    combine: &[String],
+    debug: bool,
+    rng: &mut R,
+) -> Result<Vec<SimResult>, Error> {
+    let validate = |schedule: &Schedule| -> Result<(), Error> {
+        if debug {
+            schedule.validate(&sample.instance)?;
+        }
+        Ok(())
+    };
 
-
-    let (dc_schedule, mut dc_cost) =  deterministic_alg(DoubleCoverage, &sample.instance);
+    let (dc_schedule, mut dc_cost) = DoubleCoverage.try_run_det(&sample.instance)?;
+    validate(&dc_schedule)?;
     if lazy {
-        dc_cost = dc_schedule.to_lazy(&sample.instance).cost();
+        dc_cost = dc_schedule.try_to_lazy(&sample.instance)?.cost();
     }
+    let wfa_cost = if wfa {
+        let (wfa_schedule, wfa_cost) = deterministic_alg(WorkFunction::new(), &sample.instance);
+        validate(&wfa_schedule)?;
+        Some(wfa_cost)
+    } else {
+        None
+    };
+    // harmonic ignores the predictions, but needs one of full length to serve all requests
+    let harmonic = sample
+        .predictions
+        .first()
+        .filter(|_| runs > 0)
+        .map(|pred| mean_and_variance(&Harmonic::new(Line), &sample.instance, pred, runs, rng));
     let results = sample
         .predictions
         .iter()
         .map(|pred| {
-            let (alg_schedule, mut alg_cost) = learning_augmented_alg(LambdaDC::new(lambda), &sample.instance, pred);
+            let (alg_schedule, mut alg_cost) = LambdaDC::new(lambda).try_run(&sample.instance, pred)?;
+            validate(&alg_schedule)?;
             if lazy {
-                alg_cost = alg_schedule.to_lazy(&sample.instance).cost();
+                alg_cost = alg_schedule.try_to_lazy(&sample.instance)?.cost();
             }
-            let (combine_schedule, mut combine_cost) = learning_augmented_alg(CombineDet::new(gamma), &sample.instance, pred);
+            let (combine_schedule, mut combine_cost) = CombineDet::new(gamma).try_run(&sample.instance, pred)?;
+            validate(&combine_schedule)?;
             if lazy {
-                combine_cost = combine_schedule.to_lazy(&sample.instance).cost();
+                combine_cost = combine_schedule.try_to_lazy(&sample.instance)?.cost();
 
             }
 
-            let eta = pred.eta(&sample.solution, &sample.instance);
+            let eta = pred.try_eta(&sample.solution, &sample.instance)?;
             let k = sample.instance.k() as f64;
-            if alg_cost as f64 > (1.0 + (k - 1.0) * lambda as f64) * (sample.opt_cost as f64 + 2.0 * eta as f64) {
+            if alg_cost > (1.0 + (k - 1.0) * lambda as f64) * (sample.opt_cost + 2.0 * eta) {
                 println!("LambdaDC does not achieve the theoretical competitive ratio: {} > (1+{})({} + 2{})",
                     alg_cost, lambda, sample.opt_cost, eta);
             }    
-            if lambda == 0.0 && eta == 0.0 && alg_cost as f64 != sample.opt_cost as f64 {
+            if lambda == 0.0 && eta == 0.0 && alg_cost != sample.opt_cost {
                 println!("LambdaDC with lambda = eta = 0, but ALG = {} != {} = OPT", alg_cost, sample.opt_cost);
             }  
-            if (alg_cost as f64) < sample.opt_cost as f64 {
+            if alg_cost < sample.opt_cost {
                 println!("LambdaDC ALG = {} < {} = OPT", alg_cost, sample.opt_cost);
             }  
-            let cost_list: Vec<(String, f64)> = vec![("DC".into(), dc_cost), ("LDC".into(), alg_cost), ("RobustFtp".into(), combine_cost)];
+            let mut cost_list: Vec<(String, f64)> = vec![("DC".into(), dc_cost), ("LDC".into(), alg_cost), ("RobustFtp".into(), combine_cost)];
+            if let Some(wfa_cost) = wfa_cost {
+                cost_list.push(("WFA".into(), wfa_cost));
+            }
+            if !combine.is_empty() {
+                let algorithms = combine
+                    .iter()
+                    .map(|name| algorithm_from_name(name, lambda))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let (combiner_schedule, mut combiner_cost) = Combiner::new(algorithms, gamma).try_run(&sample.instance, pred)?;
+                validate(&combiner_schedule)?;
+                if lazy {
+                    combiner_cost = combiner_schedule.try_to_lazy(&sample.instance)?.cost();
+                }
+                cost_list.push(("Combiner".into(), combiner_cost));
+            }
+            if let Some((mean, variance)) = harmonic {
+                let (combine_mean, combine_variance) = mean_and_variance(&RandCombine::new(lambda, beta), &sample.instance, pred, runs, rng);
+                cost_list.push(("Harmonic".into(), mean));
+                cost_list.push(("HarmonicVar".into(), variance));
+                cost_list.push(("RandCombine".into(), combine_mean));
+                cost_list.push(("RandCombineVar".into(), combine_variance));
+                let (rand_mean, rand_variance) = mean_and_variance(&CombineRand::new(beta), &sample.instance, pred, runs, rng);
+                cost_list.push(("CombineRand".into(), rand_mean));
+                cost_list.push(("CombineRandVar".into(), rand_variance));
+            }
+            
             
-            let res = SimResult {
-                instance: sample.instance.clone(),
+            Ok(SimResult {
+                k: sample.instance.k(),
+                length: sample.instance.length(),
                 opt_cost: sample.opt_cost,
                 eta,
                 alg_costs: cost_list,
                 lambda,
-            };
-            res.into()
+            })
         })
-        .collect::<Vec<SimResult>>();
+        .collect::<Result<Vec<SimResult>, Error>>();
     results
-}
\ No newline at end of file
+}
+
+/// Simulates Double Coverage and its learning-augmented variant on the circle.
+pub fn simulate_kserver_circle(sample: &Sample<Circle>, lambda: f32) -> Vec<SimResult> {
+    let circle = *sample.instance.metric();
+    let (_, dc_cost) = deterministic_alg(DCCircle::new(circle), &sample.instance);
+    sample
+        .predictions
+        .iter()
+        .map(|pred| {
+            let (_, alg_cost) = learning_augmented_alg(LambdaDCCircle::new(circle, lambda), &sample.instance, pred);
+            let eta = pred.eta(&sample.solution, &sample.instance);
+            // positions on the circle are wrapped, which introduces small rounding errors
+            if alg_cost < sample.opt_cost * (1.0 - 1e-6) {
+                println!("LambdaDCCircle ALG = {} < {} = OPT", alg_cost, sample.opt_cost);
+            }
+            SimResult {
+                k: sample.instance.k(),
+                length: sample.instance.length(),
+                opt_cost: sample.opt_cost,
+                eta,
+                alg_costs: vec![("DC".into(), dc_cost), ("LDC".into(), alg_cost)],
+                lambda,
+            }
+        })
+        .collect()
+}
+
+/// Simulates Double Coverage and its learning-augmented variant on a tree.
+pub fn simulate_kserver_tree(sample: &Sample<Tree>, lambda: f32) -> Vec<SimResult> {
+    let tree = sample.instance.metric().clone();
+    let (_, dc_cost) = deterministic_alg(DCTree::new(tree.clone()), &sample.instance);
+    sample
+        .predictions
+        .iter()
+        .map(|pred| {
+            let (_, alg_cost) = learning_augmented_alg(LambdaDCTree::new(tree.clone(), lambda), &sample.instance, pred);
+            let eta = pred.eta(&sample.solution, &sample.instance);
+            SimResult {
+                k: sample.instance.k(),
+                length: sample.instance.length(),
+                opt_cost: sample.opt_cost,
+                eta,
+                alg_costs: vec![("DC".into(), dc_cost), ("LDC".into(), alg_cost)],
+                lambda,
+            }
+        })
+        .collect()
+}
+
+/// Simulates the greedy algorithm and following the predictions in the Euclidean plane.
+pub fn simulate_kserver_plane(sample: &Sample<Plane>, lambda: f32) -> Vec<SimResult> {
+    let (_, greedy_cost) = deterministic_alg(Greedy::new(Plane), &sample.instance);
+    sample
+        .predictions
+        .iter()
+        .map(|pred| {
+            let (_, ftp_cost) = learning_augmented_alg(FollowPrediction::new(Plane), &sample.instance, pred);
+            let eta = pred.eta(&sample.solution, &sample.instance);
+            SimResult {
+                k: sample.instance.k(),
+                length: sample.instance.length(),
+                opt_cost: sample.opt_cost,
+                eta,
+                alg_costs: vec![("Greedy".into(), greedy_cost), ("FtP".into(), ftp_cost)],
+                lambda,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,278 @@
+use crate::algorithms::{DoubleCoverage, LambdaDC, PredAlgorithm};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serverlib::prelude::*;
+
+pub fn randomized_alg<M: Metric, A: RandAlgorithm<M>, R: Rng>(
+    alg: A,
+    instance: &Instance<M>,
+    prediction: &Prediction,
+    rng: &mut R,
+) -> (Schedule<M::Point>, f64) {
+    alg.run_rand(instance, prediction, rng)
+}
+
+/// Runs a randomized algorithm `runs` times and returns the mean and the (sample) variance of its costs.
+pub fn mean_and_variance<M: Metric, A: RandAlgorithm<M>, R: Rng>(
+    alg: &A,
+    instance: &Instance<M>,
+    prediction: &Prediction,
+    runs: usize,
+    rng: &mut R,
+) -> (f64, f64) {
+    assert!(runs > 0, "At least one run is required");
+    let costs: Vec<f64> = (0..runs)
+        .map(|_| alg.run_rand(instance, prediction, rng).1)
+        .collect();
+    let mean = costs.iter().sum::<f64>() / runs as f64;
+    let variance = if runs > 1 {
+        costs.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / (runs - 1) as f64
+    } else {
+        0.0
+    };
+    (mean, variance)
+}
+
+/// A randomized online algorithm for the k-server problem in the metric space `M`.
+///
+/// All random choices are drawn from the given `rng`, so runs are reproducible for a seeded generator.
+pub trait RandAlgorithm<M: Metric = Line> {
+    fn run_rand<R: Rng>(
+        &self,
+        instance: &Instance<M>,
+        pred: &Prediction,
+        rng: &mut R,
+    ) -> (Schedule<M::Point>, f64) {
+        let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
+        let mut costs: f64 = 0.0;
+
+        for (&req, &pred) in instance.requests().iter().zip(pred) {
+            let current = schedule.last().unwrap();
+            let (mut next, cost) = self.next_rand_move(current, req, pred, rng);
+            costs += cost;
+            next.normalize();
+            schedule.append_config(next);
+        }
+
+        (schedule, costs)
+    }
+
+    fn next_rand_move<R: Rng>(
+        &self,
+        current: &ServerConfiguration<M::Point>,
+        next_request: Request<M::Point>,
+        prediction: usize,
+        rng: &mut R,
+    ) -> (ServerConfiguration<M::Point>, f64);
+}
+
+/// The harmonic algorithm: serves each request with a random server, chosen with probability
+/// inversely proportional to its distance. Works in any metric space.
+#[derive(Debug, Clone)]
+pub struct Harmonic<M: Metric> {
+    metric: M,
+}
+
+impl<M: Metric> Harmonic<M> {
+    pub fn new(metric: M) -> Harmonic<M> {
+        Harmonic { metric }
+    }
+}
+
+impl<M: Metric> RandAlgorithm<M> for Harmonic<M> {
+    fn next_rand_move<R: Rng>(
+        &self,
+        current: &ServerConfiguration<M::Point>,
+        req: Request<M::Point>,
+        _: usize,
+        rng: &mut R,
+    ) -> (ServerConfiguration<M::Point>, f64) {
+        let distances: Vec<f32> = current
+            .into_iter()
+            .map(|server| self.metric.distance(server, req.pos()))
+            .collect();
+        if distances.contains(&0.0) {
+            return (current.clone(), 0.0);
+        }
+        let weights = distances.iter().map(|d| 1.0 / d);
+        let chosen = WeightedIndex::new(weights).unwrap().sample(rng);
+        let mut res = current.clone();
+        res[chosen] = *req.pos();
+        (res, distances[chosen] as f64)
+    }
+}
+
+/// Randomly combines `LambdaDC` and `DoubleCoverage` by following one of them at a time.
+///
+/// Both algorithms are simulated in parallel. Their probabilities are updated multiplicatively
+/// with the learning rate `beta`, and the combination switches lazily as in Blum and Burch,
+/// "On-line Learning and the Metrical Task System Problem".
+#[derive(Debug, Clone)]
+pub struct RandCombine {
+    lambda: f32,
+    beta: f64,
+}
+
+impl RandCombine {
+    pub fn new(lambda: f32, beta: f64) -> RandCombine {
+        assert!(
+            beta > 0.0 && beta < 1.0,
+            "The learning rate must be in (0, 1)"
+        );
+        RandCombine { lambda, beta }
+    }
+
+    /// Combines follow-the-prediction, which is `LambdaDC` with `lambda = 0`, and `DoubleCoverage`.
+    pub fn robust_ftp(beta: f64) -> RandCombine {
+        RandCombine::new(0.0, beta)
+    }
+}
+
+impl RandAlgorithm for RandCombine {
+    fn run_rand<R: Rng>(
+        &self,
+        instance: &Instance,
+        prediction: &Prediction,
+        rng: &mut R,
+    ) -> (Schedule, f64) {
+        let ldc = LambdaDC::new(self.lambda);
+        combine_experts(
+            &[&ldc, &DoubleCoverage],
+            self.beta,
+            instance,
+            prediction,
+            rng,
+        )
+    }
+
+    fn next_rand_move<R: Rng>(
+        &self,
+        current: &ServerConfiguration,
+        _next_request: Request,
+        _request_index: usize,
+        _rng: &mut R,
+    ) -> (ServerConfiguration, f64) {
+        (current.clone(), 0.0)
+    }
+}
+
+/// Simulates all `experts` in parallel and follows a single one at a time.
+///
+/// The costs of each step are normalized by (an upper bound on) the diameter of the points seen so far.
+/// If the probability of the followed expert decreases, the algorithm switches to another one with the
+/// relative decrease as probability, preferring experts whose probability increased the most.
+pub(crate) fn combine_experts<M: Metric, R: Rng>(
+    experts: &[&dyn PredAlgorithm<M>],
+    beta: f64,
+    instance: &Instance<M>,
+    prediction: &Prediction,
+    rng: &mut R,
+) -> (Schedule<M::Point>, f64) {
+    let metric = instance.metric();
+    let initial = instance.initial_positions();
+    let n = experts.len();
+    let mut schedule = Schedule::with_initial_config(initial.clone());
+    let mut configs = vec![initial.clone(); n];
+    let mut losses = vec![0.0; n];
+    let mut probs = vec![1.0 / n as f64; n];
+    let mut current = rng.gen_range(0..n);
+    for expert in experts {
+        expert.reset();
+    }
+
+    // the diameter is at most twice the largest distance to a fixed point
+    let origin = initial[0];
+    let mut radius = initial
+        .into_iter()
+        .map(|s| metric.distance(&origin, s))
+        .fold(0.0, f32::max);
+
+    for (&req, &pred) in instance.requests().iter().zip(prediction) {
+        radius = radius.max(metric.distance(&origin, req.pos()));
+        let diameter = 2.0 * radius as f64;
+        for (i, expert) in experts.iter().enumerate() {
+            let (mut next, cost) = expert.next_move(&configs[i], req, pred);
+            next.normalize();
+            configs[i] = next;
+            if diameter > 0.0 {
+                losses[i] += cost / diameter;
+            }
+        }
+
+        // probabilities proportional to (1 - beta)^loss, computed relative to the smallest loss
+        let min_loss = losses.iter().cloned().fold(f64::INFINITY, f64::min);
+        let weights: Vec<f64> = losses
+            .iter()
+            .map(|l| (1.0 - beta).powf(l - min_loss))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        let new_probs: Vec<f64> = weights.iter().map(|w| w / total).collect();
+
+        if new_probs[current] < probs[current]
+            && rng.gen::<f64>() < (probs[current] - new_probs[current]) / probs[current]
+        {
+            let gains = (0..n).map(|j| (new_probs[j] - probs[j]).max(0.0));
+            if let Ok(dist) = WeightedIndex::new(gains) {
+                current = dist.sample(rng);
+            }
+        }
+        probs = new_probs;
+        schedule.append_config(configs[current].clone());
+    }
+
+    let costs = schedule.metric_cost(metric);
+    (schedule, costs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_harmonic() {
+        let instance = Instance::from((vec![20, 80, 30, 70, 60, 50], vec![50, 50]));
+        let pred = Prediction::from(vec![0; 6]);
+        let mut rng = StdRng::seed_from_u64(42);
+        let (schedule, cost) = randomized_alg(Harmonic::new(Line), &instance, &pred, &mut rng);
+        assert_eq!(cost, schedule.cost());
+        // each request is served by exactly one server
+        for (config, req) in schedule.into_iter().skip(1).zip(instance.requests()) {
+            assert!(config.into_iter().any(|s| s == *req.pos()));
+        }
+
+        // the request is already covered by a server
+        let instance = Instance::from((vec![50], vec![10, 50]));
+        let (_, cost) =
+            Harmonic::new(Line).run_rand(&instance, &Prediction::from(vec![0]), &mut rng);
+        assert_eq!(0.0, cost);
+    }
+
+    #[test]
+    fn test_rand_combine() {
+        let instance = Instance::from((vec![20, 80, 40, 64, 10, 90], vec![50, 50]));
+        let pred = Prediction::from(vec![0, 1, 0, 1, 0, 1]);
+        let alg = RandCombine::new(0.5, 0.5);
+        for seed in 0..10 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (schedule, cost) = alg.run_rand(&instance, &pred, &mut rng);
+            assert_eq!(cost, schedule.cost());
+            for (config, req) in schedule.into_iter().skip(1).zip(instance.requests()) {
+                assert!(config.into_iter().any(|s| s == *req.pos()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_mean_and_variance() {
+        // with a single server, harmonic is deterministic
+        let instance = Instance::from((vec![20, 80, 30], vec![50]));
+        let pred = Prediction::from(vec![0; 3]);
+        let mut rng = StdRng::seed_from_u64(0);
+        let (mean, variance) =
+            mean_and_variance(&Harmonic::new(Line), &instance, &pred, 5, &mut rng);
+        assert_eq!(30.0 + 60.0 + 50.0, mean);
+        assert_eq!(0.0, variance);
+    }
+}
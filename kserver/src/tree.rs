@@ -0,0 +1,206 @@
+use crate::algorithms::{DetAlgorithm, PredAlgorithm};
+use serverlib::prelude::*;
+use serverlib::tree::EPS;
+
+/// Double Coverage for tree metrics: all unblocked servers move towards the request at the same speed.
+///
+/// A server is blocked if another server lies on its path to the request and is closer to it.
+#[derive(Debug, Clone)]
+pub struct DCTree {
+    tree: Tree,
+}
+
+impl DCTree {
+    pub fn new(tree: Tree) -> DCTree {
+        DCTree { tree }
+    }
+}
+
+impl DetAlgorithm<Tree> for DCTree {
+    fn next_det_move(
+        &self,
+        current: &ServerConfiguration<TreePoint>,
+        req: Request<TreePoint>,
+    ) -> (ServerConfiguration<TreePoint>, f64) {
+        serve(&self.tree, current, *req.pos(), 1.0, None)
+    }
+}
+
+/// The learning-augmented variant of `DCTree`.
+///
+/// The unblocked server on the path from the predicted server to the request moves at speed one,
+/// all other unblocked servers at speed `lambda`.
+#[derive(Debug, Clone)]
+pub struct LambdaDCTree {
+    tree: Tree,
+    lambda: f32,
+}
+
+impl LambdaDCTree {
+    pub fn new(tree: Tree, lambda: f32) -> LambdaDCTree {
+        LambdaDCTree { tree, lambda }
+    }
+}
+
+impl PredAlgorithm<Tree> for LambdaDCTree {
+    fn next_move(
+        &self,
+        current: &ServerConfiguration<TreePoint>,
+        req: Request<TreePoint>,
+        predicted: usize,
+    ) -> (ServerConfiguration<TreePoint>, f64) {
+        serve(
+            &self.tree,
+            current,
+            *req.pos(),
+            self.lambda,
+            Some(predicted),
+        )
+    }
+}
+
+/// Moves the unblocked servers towards `target` until one of them reaches it.
+///
+/// The movement is split into phases in which the set of unblocked servers does not change,
+/// i.e., a phase ends as soon as a moving server reaches a node.
+fn serve(
+    tree: &Tree,
+    current: &ServerConfiguration<TreePoint>,
+    target: TreePoint,
+    lambda: f32,
+    predicted: Option<usize>,
+) -> (ServerConfiguration<TreePoint>, f64) {
+    let mut res = current.clone();
+    loop {
+        if let Some(i) = (0..res.size()).find(|&i| tree.distance(&res[i], &target) <= EPS) {
+            res[i] = target;
+            break;
+        }
+        let unblocked = unblocked_servers(tree, &res, &target);
+        let fast = predicted.and_then(|p| {
+            unblocked
+                .iter()
+                .copied()
+                .filter(|&i| tree.on_path(&res[i], &res[p], &target))
+                .min_by(|&a, &b| {
+                    tree.distance(&res[a], &target)
+                        .partial_cmp(&tree.distance(&res[b], &target))
+                        .unwrap()
+                })
+        });
+        let speeds: Vec<(usize, f32)> = unblocked
+            .into_iter()
+            .map(|i| match fast {
+                Some(f) if f != i => (i, lambda),
+                _ => (i, 1.0),
+            })
+            .filter(|&(_, speed)| speed > 0.0)
+            .collect();
+        let time = speeds
+            .iter()
+            .map(|&(i, speed)| tree.distance(&res[i], &tree.path(&res[i], &target)[1]) / speed)
+            .fold(f32::INFINITY, f32::min);
+        for (i, speed) in speeds {
+            res[i] = tree.move_towards(&res[i], &target, speed * time);
+        }
+    }
+    let costs = tree.config_distance(current, &res);
+    (res, costs)
+}
+
+/// The servers without another server on their path to `target`.
+///
+/// Of several servers at the same position, only the one with the lowest index is unblocked.
+fn unblocked_servers(
+    tree: &Tree,
+    config: &ServerConfiguration<TreePoint>,
+    target: &TreePoint,
+) -> Vec<usize> {
+    let distances: Vec<f32> = config
+        .into_iter()
+        .map(|server| tree.distance(server, target))
+        .collect();
+    (0..config.size())
+        .filter(|&i| {
+            !(0..config.size()).any(|j| {
+                j != i
+                    && tree.on_path(&config[j], &config[i], target)
+                    && (distances[j] < distances[i] - EPS
+                        || (j < i && distances[j] <= distances[i] + EPS))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::*;
+
+    /// The path 0 - 1 - ... - 10 with edges of length 10, i.e., node `i` is at position `10 * i`.
+    fn get_path() -> Tree {
+        let edges: Vec<(usize, usize, f32)> = (0..10).map(|i| (i, i + 1, 10.0)).collect();
+        Tree::from_edges(11, &edges).unwrap()
+    }
+
+    /// A star with center 0 and leaves 1, 2, 3 at distance 2.
+    fn get_star() -> Tree {
+        Tree::from_edges(4, &[(0, 1, 2.0), (0, 2, 2.0), (0, 3, 2.0)]).unwrap()
+    }
+
+    fn get_instance(tree: &Tree, requests: Vec<usize>, initial: Vec<usize>) -> Instance<Tree> {
+        Instance::with_metric(
+            tree.clone(),
+            requests
+                .into_iter()
+                .map(|r| Request::Simple(TreePoint::from(r)))
+                .collect(),
+            ServerConfiguration::new(initial.into_iter().map(TreePoint::from).collect()),
+        )
+    }
+
+    #[test]
+    fn test_dc_tree_equals_dc_on_path() {
+        let tree = get_path();
+        let instance = get_instance(&tree, vec![2, 8, 3, 7, 6, 5], vec![5, 5]);
+        let (schedule, cost) = deterministic_alg(DCTree::new(tree), &instance);
+        let line = Instance::from((vec![20, 80, 30, 70, 60, 50], vec![50, 50]));
+        let (line_schedule, line_cost) = deterministic_alg(DoubleCoverage, &line);
+        assert_eq!(line_cost, cost);
+        for (config, line_config) in schedule.into_iter().zip(&line_schedule) {
+            let mut positions: Vec<f32> =
+                config.into_iter().map(|p| 10.0 * p.node() as f32).collect();
+            positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert_eq!(&ServerConfiguration::new(positions), line_config);
+        }
+    }
+
+    #[test]
+    fn test_dc_tree_on_star() {
+        let tree = get_star();
+        let instance = get_instance(&tree, vec![0, 1], vec![1, 2, 3]);
+        let (schedule, cost) = deterministic_alg(DCTree::new(tree), &instance);
+        assert_eq!(8.0, cost);
+        assert_eq!(
+            &ServerConfiguration::new(vec![1.into(), 0.into(), 0.into()]),
+            schedule.last().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_lambda_dc_tree_on_star() {
+        let tree = get_star();
+        let instance = get_instance(&tree, vec![0, 1], vec![1, 2, 3]);
+        let pred = Prediction::from(vec![1, 0]);
+        let (schedule, cost) =
+            learning_augmented_alg(LambdaDCTree::new(tree.clone(), 0.5), &instance, &pred);
+        let after_first =
+            ServerConfiguration::new(vec![tree.point(1, 1.0), 0.into(), tree.point(3, 1.0)]);
+        assert_eq!(&after_first, schedule.into_iter().nth(1).as_ref().unwrap());
+        // the server on the edge of leaf 1 blocks the other servers
+        assert_eq!(5.0, cost);
+
+        let (_, cost) = learning_augmented_alg(LambdaDCTree::new(tree, 0.0), &instance, &pred);
+        assert_eq!(2.0, cost);
+    }
+}
@@ -1,4 +1,6 @@
 use serverlib::prelude::*;
+use std::error::Error;
+use std::fmt;
 
 macro_rules! min {
     ($x: expr) => ($x);
@@ -12,16 +14,75 @@ macro_rules! min {
     }}
 }
 
-pub fn learning_augmented_alg<A: KTaxiPredAlgorithm>(alg: A, instance: &Instance, prediction: &Prediction) -> (Schedule, f64) {
-    alg.run(instance, prediction)
+#[derive(Debug, Clone)]
+pub struct KTaxiError {
+    msg: String,
 }
 
-pub fn deterministic_alg<A: KTaxiDetAlgorithm>(alg: A, instance: &Instance) -> (Schedule, f64) {
-    alg.run_det(instance)
+impl fmt::Display for KTaxiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unsupported instance: {}!", self.msg)
+    }
+}
+
+impl Error for KTaxiError {
+    fn description(&self) -> &str {
+        "Unsupported instance!"
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        None
+    }
+}
+
+impl KTaxiError {
+    pub fn new(msg: String) -> KTaxiError {
+        KTaxiError { msg }
+    }
 }
 
+impl From<serverlib::Error> for KTaxiError {
+    fn from(e: serverlib::Error) -> KTaxiError {
+        KTaxiError::new(e.to_string())
+    }
+}
+
+/// Runs `alg` on `instance`, whose relocations are charged according to `model`.
+pub fn learning_augmented_alg<A: KTaxiPredAlgorithm>(
+    alg: A,
+    instance: &Instance,
+    prediction: &Prediction,
+    model: CostModel,
+) -> Result<(Schedule, f64), KTaxiError> {
+    alg.validate(instance.k())?;
+    Ok(alg.try_run(instance, prediction, model)?)
+}
+
+/// Runs `alg` on `instance`, whose relocations are charged according to `model`.
+pub fn deterministic_alg<A: KTaxiDetAlgorithm>(alg: A, instance: &Instance, model: CostModel) -> Result<(Schedule, f64), KTaxiError> {
+    alg.validate(instance.k())?;
+    Ok(alg.try_run_det(instance, model)?)
+}
+
+/// Sorts the servers and returns the new index of the active server.
+///
+/// Servers at the same position keep their order, so the active server only changes its index if it passes another one.
+fn normalize_active(config: &mut ServerConfiguration, active: usize) -> usize {
+    let mut order: Vec<usize> = (0..config.size()).collect();
+    order.sort_by(|&a, &b| config[a].partial_cmp(&config[b]).unwrap());
+    config.normalize();
+    order.iter().position(|&i| i == active).unwrap()
+}
+
+/// A deterministic online algorithm for the k-taxi problem on the line.
 pub trait KTaxiDetAlgorithm {
-    fn run_det(&self, instance: &Instance) -> (Schedule, f64) {
+    /// Checks whether the algorithm supports instances with `k` servers.
+    fn validate(&self, _k: usize) -> Result<(), KTaxiError> {
+        Ok(())
+    }
+
+    /// Runs the algorithm, where every relocation is charged according to `model`.
+    fn run_det(&self, instance: &Instance, model: CostModel) -> (Schedule, f64) {
         let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
         let mut costs: f64 = 0.0;
 
@@ -30,50 +91,92 @@ pub trait KTaxiDetAlgorithm {
             let current = schedule.last().unwrap();
             let (new_active, mut next, cost) = self.next_det_move(current, active, req);
             //println!("{}", cost);
-            costs += cost;
-            active = new_active;
-            if next[0] > next[1] {
-                active = 1 - active;
-                next.normalize();
-            }
+            costs += cost + model.relocation_cost(instance.metric(), &req);
+            active = normalize_active(&mut next, new_active);
             schedule.append_config(next);
         }
 
         (schedule, costs)
     }
 
+    /// Like `run_det`, but fails instead of panicking if a request cannot be served.
+    fn try_run_det(&self, instance: &Instance, model: CostModel) -> Result<(Schedule, f64), serverlib::Error> {
+        let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
+        let mut costs: f64 = 0.0;
+
+        let mut active = 0;
+        for &req in instance.requests() {
+            let current = schedule.last().ok_or(serverlib::Error::EmptySchedule)?;
+            let (new_active, mut next, cost) = self.try_next_det_move(current, active, req)?;
+            costs += cost + model.relocation_cost(instance.metric(), &req);
+            active = normalize_active(&mut next, new_active);
+            schedule.append_config(next);
+        }
+
+        Ok((schedule, costs))
+    }
+
     fn next_det_move(
         &self,
         current: &ServerConfiguration,
         active: usize,
         next_request: Request,
     ) -> (usize, ServerConfiguration, f64);
+
+    fn try_next_det_move(
+        &self,
+        current: &ServerConfiguration,
+        active: usize,
+        next_request: Request,
+    ) -> Result<(usize, ServerConfiguration, f64), serverlib::Error> {
+        Ok(self.next_det_move(current, active, next_request))
+    }
 }
 
 
 
+/// A learning-augmented online algorithm for the k-taxi problem on the line.
 pub trait KTaxiPredAlgorithm {
-    fn run(&self, instance: &Instance, prediction: &Prediction) -> (Schedule, f64) {
+    /// Checks whether the algorithm supports instances with `k` servers.
+    fn validate(&self, _k: usize) -> Result<(), KTaxiError> {
+        Ok(())
+    }
+
+    /// Runs the algorithm, where every relocation is charged according to `model`.
+    fn run(&self, instance: &Instance, prediction: &Prediction, model: CostModel) -> (Schedule, f64) {
         let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
         let mut costs: f64 = 0.0;
 
         let mut active = 0;
-        for (&req, &pred) in instance.requests().into_iter().zip(prediction.into_iter()) {
+        for (&req, &pred) in instance.requests().iter().zip(prediction) {
             let current = schedule.last().unwrap();
             let (new_active, mut next, cost) = self.next_move(current, active, req, pred);
             //println!("{}", cost);
-            costs += cost;
-            active = new_active;
-            if next[0] > next[1] {
-                active = 1 - active;
-                next.normalize();
-            }
+            costs += cost + model.relocation_cost(instance.metric(), &req);
+            active = normalize_active(&mut next, new_active);
             schedule.append_config(next);
         }
 
         (schedule, costs)
     }
 
+    /// Like `run`, but fails instead of panicking if a request cannot be served.
+    fn try_run(&self, instance: &Instance, prediction: &Prediction, model: CostModel) -> Result<(Schedule, f64), serverlib::Error> {
+        let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
+        let mut costs: f64 = 0.0;
+
+        let mut active = 0;
+        for (&req, &pred) in instance.requests().iter().zip(prediction) {
+            let current = schedule.last().ok_or(serverlib::Error::EmptySchedule)?;
+            let (new_active, mut next, cost) = self.try_next_move(current, active, req, pred)?;
+            costs += cost + model.relocation_cost(instance.metric(), &req);
+            active = normalize_active(&mut next, new_active);
+            schedule.append_config(next);
+        }
+
+        Ok((schedule, costs))
+    }
+
     fn next_move(
         &self,
         current: &ServerConfiguration,
@@ -81,13 +184,36 @@ pub trait KTaxiPredAlgorithm {
         next_request: Request,
         prediction: usize,
     ) -> (usize, ServerConfiguration, f64);
+
+    fn try_next_move(
+        &self,
+        current: &ServerConfiguration,
+        active: usize,
+        next_request: Request,
+        prediction: usize,
+    ) -> Result<(usize, ServerConfiguration, f64), serverlib::Error> {
+        Ok(self.next_move(current, active, next_request, prediction))
+    }
 }
 
 
 
+fn validate_two_servers(name: &str, k: usize) -> Result<(), KTaxiError> {
+    if k == 2 {
+        Ok(())
+    } else {
+        Err(KTaxiError::new(format!("{} requires exactly 2 servers, but k = {}", name, k)))
+    }
+}
+
+/// Biased Double Coverage for two servers: the passive server moves twice as fast as the active one.
 pub struct BiasedDC;
 
 impl KTaxiDetAlgorithm for BiasedDC {
+    fn validate(&self, k: usize) -> Result<(), KTaxiError> {
+        validate_two_servers("BiasedDC", k)
+    }
+
     fn next_det_move(
         &self,
         current: &ServerConfiguration,
@@ -104,33 +230,29 @@ impl KTaxiDetAlgorithm for BiasedDC {
         let mut cost: f64 = 0.0;
         if res[active] != pos && res[passive] != pos {
             let dp = min!(
-                2.0 * (pos - current[active]).abs() as f32,
-                (pos - current[passive]).abs() as f32
+                2.0 * (pos - current[active]).abs(),
+                (pos - current[passive]).abs()
             );
-            let da = dp / 2.0 as f32;
+            let da = dp / 2.0_f32;
 
             res[active] += da * ((pos - current[active]) / (pos - current[active]).abs());
             res[passive] += dp * ((pos - current[passive]) / (pos - current[passive]).abs());
             cost = (da + dp) as f64;
             //println!("biasedDC: da={} dp={}", da, dp);
         }
-        let new_active;
-        if res[active] == pos {
-            new_active = active;
-        } else {
-            new_active = passive;
-        }
+        let new_active = if res[active] == pos { active } else { passive };
 
         res[new_active] = match req {
             Request::Simple(x) => x,
             Request::Relocation(_, x) => x,
         };
-        return (new_active, res, cost);
+        (new_active, res, cost)
     }
 }
 
 
 
+/// The learning-augmented variant of `BiasedDC` for two servers.
 pub struct LambdaBiasedDC {
     lambda: f32,
 }
@@ -142,6 +264,10 @@ impl LambdaBiasedDC {
 }
 
 impl KTaxiPredAlgorithm for LambdaBiasedDC {
+    fn validate(&self, k: usize) -> Result<(), KTaxiError> {
+        validate_two_servers("LambdaBiasedDC", k)
+    }
+
     fn next_move(
         &self,
         current: &ServerConfiguration,
@@ -191,27 +317,130 @@ impl KTaxiPredAlgorithm for LambdaBiasedDC {
         } else {
             //println!("Taxi already on request");
         }
-        let new_active;
-        if res[active] == pos {
-            new_active = active;
-        } else {
-            new_active = passive;
-        }
+        let new_active = if res[active] == pos { active } else { passive };
 
         res[new_active] = match req {
             Request::Simple(x) => x,
             Request::Relocation(_, x) => x,
         };
 
-        return (new_active, res, cost);
+        (new_active, res, cost)
+    }
+}
+
+/// Biased Double Coverage for an arbitrary number of servers.
+///
+/// Only the servers adjacent to the request move towards it, where the active server moves at speed one
+/// and passive servers at speed two.
+pub struct KBiasedDC;
+
+impl KTaxiDetAlgorithm for KBiasedDC {
+    /// Panics if the configuration has no servers, see `try_next_det_move`.
+    fn next_det_move(
+        &self,
+        current: &ServerConfiguration,
+        active: usize,
+        req: Request,
+    ) -> (usize, ServerConfiguration, f64) {
+        self.try_next_det_move(current, active, req)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_next_det_move(
+        &self,
+        current: &ServerConfiguration,
+        active: usize,
+        req: Request,
+    ) -> Result<(usize, ServerConfiguration, f64), serverlib::Error> {
+        serve_biased(current, active, req, 1.0, None)
+    }
+}
+
+/// The learning-augmented variant of `KBiasedDC`.
+///
+/// The speed of an adjacent server is the product of two factors: one for the active server and `1 + lambda`
+/// for passive servers, and one if the server is on the side of the predicted server and `lambda` otherwise.
+/// Hence, it equals `KBiasedDC` for `lambda = 1` and follows the predictions for `lambda = 0`.
+pub struct LambdaKBiasedDC {
+    lambda: f32,
+}
+
+impl LambdaKBiasedDC {
+    pub fn new(lambda: f32) -> LambdaKBiasedDC {
+        LambdaKBiasedDC { lambda }
+    }
+}
+
+impl KTaxiPredAlgorithm for LambdaKBiasedDC {
+    /// Panics if the configuration has no servers, see `try_next_move`.
+    fn next_move(
+        &self,
+        current: &ServerConfiguration,
+        active: usize,
+        req: Request,
+        predicted: usize,
+    ) -> (usize, ServerConfiguration, f64) {
+        self.try_next_move(current, active, req, predicted)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_next_move(
+        &self,
+        current: &ServerConfiguration,
+        active: usize,
+        req: Request,
+        predicted: usize,
+    ) -> Result<(usize, ServerConfiguration, f64), serverlib::Error> {
+        serve_biased(current, active, req, self.lambda, Some(predicted))
     }
 }
 
+/// Moves the servers adjacent to the request towards it until one of them reaches it and gets relocated.
+fn serve_biased(
+    current: &ServerConfiguration,
+    active: usize,
+    req: Request,
+    lambda: f32,
+    predicted: Option<usize>,
+) -> Result<(usize, ServerConfiguration, f64), serverlib::Error> {
+    let pos = *req.pos();
+    let mut res = current.clone();
+    let (new_active, cost) = match current.try_adjacent_servers(&req)? {
+        (Some(i), Some(j)) if i == j => (i, 0.0),
+        (Some(i), Some(j)) => {
+            let speed = |s: usize| {
+                let bias = if s == active { 1.0 } else { 1.0 + lambda };
+                match predicted {
+                    Some(p) if (current[p] < pos) != (s == i) => bias * lambda,
+                    _ => bias,
+                }
+            };
+            let (speed_i, speed_j) = (speed(i), speed(j));
+            let time_i = (pos - current[i]) / speed_i;
+            let time_j = (current[j] - pos) / speed_j;
+            if time_i <= time_j {
+                res[i] = pos;
+                res[j] -= speed_j * time_i;
+                (i, pos - current[i] + speed_j * time_i)
+            } else {
+                res[i] += speed_i * time_j;
+                res[j] = pos;
+                (j, speed_i * time_j + current[j] - pos)
+            }
+        }
+        (Some(i), None) | (None, Some(i)) => {
+            res[i] = pos;
+            (i, (pos - current[i]).abs())
+        }
+        (None, None) => return Err(serverlib::Error::NoServers),
+    };
+    res[new_active] = *req.end();
+    Ok((new_active, res, cost as f64))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::error::Error;
-
 
     #[test]
     fn test_biased_dc() {
@@ -225,10 +454,78 @@ mod tests {
                 vec![10, 30],
                 vec![0, 25],
             ]),
-            alg.run_det(&instance).0
+            alg.run_det(&instance, CostModel::Easy).0
         )
     }
 
+    #[test]
+    fn test_biased_dc_rejects_three_servers() {
+        let instance = Instance::from((vec![(0, 0)], vec![0, 10, 20]));
+        assert!(deterministic_alg(BiasedDC, &instance, CostModel::Easy).is_err());
+        let pred = Prediction::from(vec![0]);
+        assert!(learning_augmented_alg(LambdaBiasedDC::new(0.5), &instance, &pred, CostModel::Easy).is_err());
+        assert!(deterministic_alg(KBiasedDC, &instance, CostModel::Easy).is_ok());
+    }
+
+    #[test]
+    fn test_k_biased_dc_three_servers() {
+        let instance = Instance::from((vec![(14, 0), (2, 30)], vec![0, 10, 20]));
+        let (schedule, cost) = deterministic_alg(KBiasedDC, &instance, CostModel::Easy).unwrap();
+        // the passive servers at 10 and 20 move at the same speed, then the active server at 0 serves 2
+        assert_eq!(
+            Schedule::from(vec![vec![0, 10, 20], vec![0, 0, 16], vec![0, 12, 30]]),
+            schedule
+        );
+        assert_eq!(8.0 + 6.0, cost);
+    }
+
+    #[test]
+    fn test_k_biased_dc_without_servers_is_an_error() {
+        let instance = Instance::from((vec![(14, 0)], Vec::<i32>::new()));
+        let err = deterministic_alg(KBiasedDC, &instance, CostModel::Easy).unwrap_err();
+        assert!(err.to_string().contains(&serverlib::Error::NoServers.to_string()));
+        let pred = Prediction::from(vec![0]);
+        assert!(learning_augmented_alg(LambdaKBiasedDC::new(0.5), &instance, &pred, CostModel::Easy).is_err());
+    }
+
+    #[test]
+    fn test_k_biased_dc_charges_relocations_in_hard_model() {
+        let instance = Instance::from((vec![(14, 0), (2, 30)], vec![0, 10, 20]));
+        let (schedule, cost) = deterministic_alg(KBiasedDC, &instance, CostModel::Hard).unwrap();
+        assert_eq!(8.0 + 6.0 + 14.0 + 28.0, cost);
+        assert_eq!(schedule.taxi_cost(&instance, CostModel::Hard), cost);
+        let pred = Prediction::from(vec![0, 2]);
+        let (schedule, cost) = learning_augmented_alg(LambdaKBiasedDC::new(0.5), &instance, &pred, CostModel::Hard).unwrap();
+        assert_eq!(schedule.taxi_cost(&instance, CostModel::Hard), cost);
+    }
+
+    #[test]
+    fn test_lambda_k_biased_dc_five_servers() {
+        let instance = Instance::from((
+            vec![(27, 27), (3, 3), (6, 45)],
+            vec![0, 10, 20, 30, 40],
+        ));
+        let pred = Prediction::from(vec![4, 0, 0]);
+        let (schedule, cost) = learning_augmented_alg(LambdaKBiasedDC::new(0.5), &instance, &pred, CostModel::Easy).unwrap();
+        assert_eq!(
+            vec![
+                vec![0.0, 10.0, 20.0, 30.0, 40.0],
+                vec![0.0, 10.0, 21.5, 27.0, 40.0],
+                vec![3.0, 8.5, 21.5, 27.0, 40.0],
+                vec![6.25, 21.5, 27.0, 40.0, 45.0],
+            ]
+            .into_iter()
+            .map(ServerConfiguration::from)
+            .collect::<Schedule>(),
+            schedule
+        );
+        assert_eq!(4.5 + 4.5 + 5.25, cost);
+
+        // lambda = 1 ignores the predictions
+        let (schedule, _) = learning_augmented_alg(LambdaKBiasedDC::new(1.0), &instance, &pred, CostModel::Easy).unwrap();
+        assert_eq!(deterministic_alg(KBiasedDC, &instance, CostModel::Easy).unwrap().0, schedule);
+    }
+
     #[test]
     fn test_lambda_biased_dc_1() {
         let instance = Instance::from((vec![(0, 0), (10, 0), (30, 30), (0, 0)], vec![0, 30]));
@@ -242,7 +539,7 @@ mod tests {
                 vec![10, 30],
                 vec![0, 25],
             ]),
-            alg.run(&instance, &pred).0
+            alg.run(&instance, &pred, CostModel::Easy).0
         )
     }
 
@@ -259,7 +556,7 @@ mod tests {
                 vec![0, 30],
                 vec![0, 30],
             ]),
-            alg.run(&instance, &pred).0
+            alg.run(&instance, &pred, CostModel::Easy).0
         )
     }
 }
@@ -0,0 +1,79 @@
+use crate::algorithms::KTaxiError;
+use serverlib::prelude::*;
+
+/// Relative tolerance for comparing costs, since positions are stored as `f32`.
+const TOLERANCE: f64 = 1e-6;
+
+/// Checks that `schedule` is a feasible solution of the k-taxi `instance` with the given cost.
+///
+/// The schedule has to start in the initial configuration, and after every request one server has to be
+/// located at its end, i.e., it has been relocated. The cost is compared to `Schedule::taxi_cost` under `model`.
+pub fn check_schedule(
+    instance: &Instance,
+    schedule: &Schedule,
+    cost: f64,
+    model: CostModel,
+) -> Result<(), KTaxiError> {
+    if schedule.len() != instance.length() + 1 {
+        return Err(KTaxiError::new(format!(
+            "the schedule has {} configurations, but there are {} requests",
+            schedule.len(),
+            instance.length()
+        )));
+    }
+    let mut initial = instance.initial_positions().clone();
+    initial.normalize();
+    let mut configs = schedule.into_iter();
+    let first = configs.next().unwrap();
+    if first != &initial {
+        return Err(KTaxiError::new(format!(
+            "the schedule starts in {:?} instead of {:?}",
+            first, initial
+        )));
+    }
+    for (i, (config, req)) in configs.zip(instance.requests()).enumerate() {
+        if config.size() != instance.k() {
+            return Err(KTaxiError::new(format!(
+                "configuration {} has {} servers instead of {}",
+                i + 1,
+                config.size(),
+                instance.k()
+            )));
+        }
+        if !config.into_iter().any(|s| s == req.end()) {
+            return Err(KTaxiError::new(format!(
+                "request {} ({}) is not served",
+                i, req
+            )));
+        }
+    }
+    let actual = schedule.try_taxi_cost(instance, model)?;
+    if (actual - cost).abs() > TOLERANCE * actual.max(1.0) {
+        return Err(KTaxiError::new(format!(
+            "the schedule costs {} instead of {}",
+            actual, cost
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_schedule_works() {
+        let instance = Instance::from((vec![(10, 30), (25, 0)], vec![0, 20]));
+        let schedule = Schedule::from(vec![vec![0, 20], vec![0, 30], vec![0, 0]]);
+        assert!(check_schedule(&instance, &schedule, 15.0, CostModel::Easy).is_ok());
+        assert!(check_schedule(&instance, &schedule, 60.0, CostModel::Hard).is_ok());
+        assert!(check_schedule(&instance, &schedule, 14.0, CostModel::Easy).is_err());
+
+        // the first relocation is not served
+        let schedule = Schedule::from(vec![vec![0, 20], vec![0, 10], vec![0, 0]]);
+        assert!(check_schedule(&instance, &schedule, 15.0, CostModel::Easy).is_err());
+
+        let schedule = Schedule::from(vec![vec![0, 20], vec![0, 30]]);
+        assert!(check_schedule(&instance, &schedule, 10.0, CostModel::Easy).is_err());
+    }
+}
@@ -1,35 +1,60 @@
 pub mod algorithms;
+pub mod checker;
 
 use samplelib::*;
+use serverlib::prelude::Schedule;
 use crate::algorithms::*;
+use crate::checker::check_schedule;
 
-pub fn simulate_ktaxi(sample: &Sample, lambda: f32) -> Vec<SimResult> {
-    let bdc = deterministic_alg(BiasedDC, &sample.instance);
-    let bdc_cost = bdc.1;
-    if bdc_cost as f32 / sample.opt_cost as f32 > 9.0 {
-        println!(
-            "competitive ratio of biasedDC > 9: {}, BiasedDC={:?}, Opt={:?}",
-            sample.instance, bdc.0, sample.solution
-        );
-    }
+/// Simulates the k-taxi algorithms on a sample.
+///
+/// By default, these are `BiasedDC` and its learning-augmented variant (columns `BDC` and `LBDC`), which
+/// only support two servers. With `general`, the generalised `KBiasedDC` and `LambdaKBiasedDC` (columns `KBDC`
+/// and `KLBDC`) are simulated instead, which support any number of servers.
+/// The algorithms are charged according to the cost model under which OPT was computed, and samples
+/// whose solution is not a feasible schedule with cost OPT are rejected. In `debug` mode, the schedules of the
+/// algorithms are validated against the instance as well, and their costs are checked in the same way as OPT.
+pub fn simulate_ktaxi(sample: &Sample, lambda: f32, general: bool, debug: bool) -> Result<Vec<SimResult>, KTaxiError> {
+    check_schedule(&sample.instance, &sample.solution, sample.opt_cost, sample.cost_model)?;
+    let model = sample.cost_model;
+    let validate = |(schedule, cost): (Schedule, f64)| -> Result<(Schedule, f64), KTaxiError> {
+        if debug {
+            schedule
+                .validate(&sample.instance)
+                .map_err(|e| KTaxiError::new(e.to_string()))?;
+            check_schedule(&sample.instance, &schedule, cost, model)?;
+        }
+        Ok((schedule, cost))
+    };
+    let (det_name, pred_name) = if general { ("KBDC", "KLBDC") } else { ("BDC", "LBDC") };
+    let det_cost = if general {
+        validate(deterministic_alg(KBiasedDC, &sample.instance, model)?)?.1
+    } else {
+        validate(deterministic_alg(BiasedDC, &sample.instance, model)?)?.1
+    };
     let results = sample
         .predictions
         .iter()
         .map(|pred| {
-            let (_, alg_cost) = learning_augmented_alg(LambdaBiasedDC::new(lambda), &sample.instance, pred);
-            let eta = pred.eta(&sample.solution, &sample.instance);
+            let eta = pred.try_eta(&sample.solution, &sample.instance)?;
 
-            let cost_list: Vec<(String, f64)> = vec![("BDC".into(), bdc_cost), ("LBDC".into(), alg_cost)];
+            let (_, alg_cost) = if general {
+                validate(learning_augmented_alg(LambdaKBiasedDC::new(lambda), &sample.instance, pred, model)?)?
+            } else {
+                validate(learning_augmented_alg(LambdaBiasedDC::new(lambda), &sample.instance, pred, model)?)?
+            };
+            let cost_list: Vec<(String, f64)> =
+                vec![(det_name.into(), det_cost), (pred_name.into(), alg_cost)];
 
-            let res = SimResult {
-                instance: sample.instance.clone(),
+            Ok(SimResult {
+                k: sample.instance.k(),
+                length: sample.instance.length(),
                 opt_cost: sample.opt_cost,
                 eta,
                 alg_costs: cost_list,
-                lambda: lambda,
-            };
-            res.into()
+                lambda,
+            })
         })
-        .collect::<Vec<SimResult>>();
+        .collect::<Result<Vec<SimResult>, KTaxiError>>();
     results
-}
\ No newline at end of file
+}
@@ -0,0 +1,104 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// The file formats of `save` and `load`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Human-readable JSON.
+    Json,
+    /// The compact binary format of bincode.
+    Binary,
+}
+
+impl Format {
+    /// JSON for files ending with `.json` and binary otherwise.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Format {
+        match path.as_ref().extension() {
+            Some(ext) if ext == "json" => Format::Json,
+            _ => Format::Binary,
+        }
+    }
+}
+
+/// Writes a value, e.g. a `Vec<Sample>`, to a file.
+pub fn save<T: Serialize, P: AsRef<Path>>(
+    value: &T,
+    path: P,
+    format: Format,
+) -> Result<(), Box<dyn Error>> {
+    let writer = BufWriter::new(File::create(path)?);
+    match format {
+        Format::Json => serde_json::to_writer(writer, value)?,
+        Format::Binary => bincode::serialize_into(writer, value)?,
+    }
+    Ok(())
+}
+
+/// Reads a value written by `save` in the same format.
+pub fn load<T: DeserializeOwned, P: AsRef<Path>>(
+    path: P,
+    format: Format,
+) -> Result<T, Box<dyn Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(match format {
+        Format::Json => serde_json::from_reader(reader)?,
+        Format::Binary => bincode::deserialize_from(reader)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sample;
+    use serverlib::prelude::*;
+
+    #[test]
+    fn save_and_load_work() -> Result<(), Box<dyn Error>> {
+        let instance = Instance::from((vec![(20, 30), (80, 80), (30, 30)], vec![50, 50]));
+        let solution = Schedule::from(vec![vec![50, 50], vec![30, 50], vec![30, 80], vec![30, 80]]);
+        let mut sample = Sample::new(instance, solution, 110.0);
+        sample.predictions = vec![Prediction::from(vec![0, 1, 0])];
+        sample.cost_model = CostModel::Hard;
+
+        for (file, format) in &[
+            ("sample.json", Format::Json),
+            ("sample.bin", Format::Binary),
+        ] {
+            let path = std::env::temp_dir().join(format!("serverlib_{}", file));
+            assert_eq!(*format, Format::from_path(&path));
+            save(&vec![sample.clone()], &path, *format)?;
+            let loaded: Vec<Sample> = load(&path, *format)?;
+            std::fs::remove_file(&path)?;
+
+            assert_eq!(1, loaded.len());
+            assert_eq!(sample.instance.requests(), loaded[0].instance.requests());
+            assert_eq!(
+                sample.instance.initial_positions(),
+                loaded[0].instance.initial_positions()
+            );
+            assert_eq!(sample.solution, loaded[0].solution);
+            assert_eq!(sample.opt_cost, loaded[0].opt_cost);
+            assert_eq!(CostModel::Hard, loaded[0].cost_model);
+            assert_eq!(
+                format!("{:?}", sample.predictions),
+                format!("{:?}", loaded[0].predictions)
+            );
+        }
+
+        let plane = Instance::with_metric(
+            Plane,
+            vec![Request::Simple(PlanePoint::new(1.0, 2.0))],
+            ServerConfiguration::new(vec![PlanePoint::new(0.0, 0.0)]),
+        );
+        let path = std::env::temp_dir().join("serverlib_plane.bin");
+        save(&plane, &path, Format::Binary)?;
+        let loaded: Instance<Plane> = load(&path, Format::Binary)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(plane.requests(), loaded.requests());
+        Ok(())
+    }
+}
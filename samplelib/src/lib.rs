@@ -1,5 +1,11 @@
+#[cfg(feature = "serde")]
+mod io;
 mod result;
 mod sample;
+mod seed;
 
+#[cfg(feature = "serde")]
+pub use io::{load, save, Format};
 pub use result::SimResult;
-pub use sample::Sample;
\ No newline at end of file
+pub use sample::Sample;
+pub use seed::{stream_rng, RngStream};
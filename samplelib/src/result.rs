@@ -1,10 +1,10 @@
-use serverlib::prelude::Instance;
-
-
+/// The costs of the algorithms for a single prediction, together with the size of the simulated instance.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimResult {
-    pub instance: Instance,
-    pub opt_cost: u32,
+    pub k: usize,
+    pub length: usize,
+    pub opt_cost: f64,
     pub eta: f64,
     pub alg_costs: Vec<(String, f64)>,
     pub lambda: f32,
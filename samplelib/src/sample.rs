@@ -1,20 +1,31 @@
 use serverlib::prelude::*;
 
 #[derive(Clone, Debug)]
-pub struct Sample {
-    pub instance: Instance,
-    pub solution: Schedule,
-    pub opt_cost: u32,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "M: serde::Serialize, M::Point: serde::Serialize",
+        deserialize = "M: serde::Deserialize<'de>, M::Point: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Sample<M: Metric = Line> {
+    pub instance: Instance<M>,
+    pub solution: Schedule<M::Point>,
+    pub opt_cost: f64,
     pub predictions: Vec<Prediction>,
+    /// The cost model under which `opt_cost` was computed.
+    pub cost_model: CostModel,
 }
 
-impl Sample {
-    pub fn new(instance: Instance, solution: Schedule, opt_cost: u32) -> Sample {
+impl<M: Metric> Sample<M> {
+    pub fn new(instance: Instance<M>, solution: Schedule<M::Point>, opt_cost: f64) -> Sample<M> {
         Sample {
             instance,
             solution,
             opt_cost,
             predictions: vec![],
+            cost_model: CostModel::Easy,
         }
     }
 }
@@ -0,0 +1,60 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// The independent random streams of a run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RngStream {
+    /// Sampling of instances.
+    Instance = 0,
+    /// Noise of the predictions and the selection of predictions per bin.
+    Prediction = 1,
+    /// Random choices of randomized algorithms.
+    Simulation = 2,
+}
+
+/// A random number generator for the `index`-th instance in the given stream.
+///
+/// With a seed, the generator only depends on the seed, the stream and the index, such that results do not
+/// depend on the order in which rayon processes the instances. Without a seed, it is seeded by the OS.
+pub fn stream_rng(seed: Option<u64>, stream: RngStream, index: usize) -> StdRng {
+    match seed {
+        Some(seed) => {
+            let mut key = [0u8; 32];
+            key[..8].copy_from_slice(&seed.to_le_bytes());
+            key[8..16].copy_from_slice(&(stream as u64).to_le_bytes());
+            key[16..24].copy_from_slice(&(index as u64).to_le_bytes());
+            StdRng::from_seed(key)
+        }
+        None => StdRng::from_entropy(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn stream_rng_is_reproducible() {
+        let sample = |seed, stream, index| {
+            let mut rng = stream_rng(Some(seed), stream, index);
+            (0..4).map(|_| rng.gen::<u64>()).collect::<Vec<u64>>()
+        };
+        assert_eq!(
+            sample(7, RngStream::Instance, 3),
+            sample(7, RngStream::Instance, 3)
+        );
+        assert_ne!(
+            sample(7, RngStream::Instance, 3),
+            sample(7, RngStream::Instance, 4)
+        );
+        assert_ne!(
+            sample(7, RngStream::Instance, 3),
+            sample(7, RngStream::Prediction, 3)
+        );
+        assert_ne!(
+            sample(7, RngStream::Instance, 3),
+            sample(8, RngStream::Instance, 3)
+        );
+    }
+}
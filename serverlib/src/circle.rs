@@ -0,0 +1,168 @@
+use crate::metric::{Metric, Point};
+use crate::request::Request;
+use crate::server_config::ServerConfiguration;
+use std::fmt;
+
+/// A point on a circle, given by its clockwise distance to `0`.
+///
+/// Points are created by `Circle::point`, which maps them into `[0, circumference)`. In contrast to
+/// the line, configurations are not sorted, since a server moving across `0` would change its index.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CirclePoint(f32);
+
+impl CirclePoint {
+    pub fn position(&self) -> f32 {
+        self.0
+    }
+}
+
+impl fmt::Display for CirclePoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Point for CirclePoint {}
+
+/// A circle (ring) with a fixed circumference.
+///
+/// ## Examples
+///
+/// ```
+/// # use serverlib::circle::Circle;
+/// # use serverlib::metric::Metric;
+/// # use serverlib::request::Request;
+/// # use serverlib::server_config::ServerConfiguration;
+/// let circle = Circle::new(100.0);
+/// assert_eq!(20.0, circle.distance(&circle.point(90.0), &circle.point(10.0)));
+/// assert_eq!(circle.point(90.0), circle.point(-10.0));
+/// let config = ServerConfiguration::new(vec![circle.point(10.0), circle.point(50.0)]);
+/// let req = Request::Simple(circle.point(95.0));
+/// assert_eq!((1, 0), circle.adjacent_servers(&config, &req));
+/// let req = Request::Simple(circle.point(30.0));
+/// assert_eq!((0, 1), circle.adjacent_servers(&config, &req));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Circle {
+    circumference: f32,
+}
+
+impl Circle {
+    pub fn new(circumference: f32) -> Circle {
+        assert!(circumference > 0.0, "The circumference must be positive");
+        Circle { circumference }
+    }
+
+    pub fn circumference(&self) -> f32 {
+        self.circumference
+    }
+
+    /// Maps an arbitrary value onto the circle.
+    pub fn point(&self, x: f32) -> CirclePoint {
+        let y = x.rem_euclid(self.circumference);
+        // rem_euclid may round up to the circumference for tiny negative values
+        if y >= self.circumference {
+            CirclePoint(0.0)
+        } else {
+            CirclePoint(y)
+        }
+    }
+
+    /// The distance from `a` to `b` in clockwise direction.
+    pub fn clockwise(&self, a: &CirclePoint, b: &CirclePoint) -> f32 {
+        self.point(b.0 - a.0).0
+    }
+
+    /// Moves `p` by `delta` in clockwise direction, or counterclockwise for a negative `delta`.
+    pub fn move_by(&self, p: &CirclePoint, delta: f32) -> CirclePoint {
+        self.point(p.0 + delta)
+    }
+
+    /// The servers adjacent to the request in counterclockwise and clockwise direction.
+    ///
+    /// In contrast to the line, there is always a server on both sides, since the circle wraps around.
+    /// If a server is located at the request, both are equal to it.
+    pub fn adjacent_servers(
+        &self,
+        config: &ServerConfiguration<CirclePoint>,
+        req: &Request<CirclePoint>,
+    ) -> (usize, usize) {
+        let pos = req.pos();
+        if let Some(i) = config.into_iter().position(|s| s == pos) {
+            return (i, i);
+        }
+        let closest = |dist: &dyn Fn(&CirclePoint) -> f32| {
+            (0..config.size())
+                .min_by(|&a, &b| dist(&config[a]).partial_cmp(&dist(&config[b])).unwrap())
+                .expect("The configuration has no servers")
+        };
+        (
+            closest(&|s| self.clockwise(s, pos)),
+            closest(&|s| self.clockwise(pos, s)),
+        )
+    }
+}
+
+impl Metric for Circle {
+    type Point = CirclePoint;
+
+    fn distance(&self, a: &CirclePoint, b: &CirclePoint) -> f32 {
+        let d = self.clockwise(a, b);
+        d.min(self.circumference - d)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::Instance;
+    use crate::pred::Prediction;
+
+    #[test]
+    fn circle_distance_wraps() {
+        let circle = Circle::new(24.0);
+        let p = |x| circle.point(x);
+        assert_eq!(2.0, circle.distance(&p(23.0), &p(1.0)));
+        assert_eq!(12.0, circle.distance(&p(0.0), &p(12.0)));
+        assert_eq!(0.0, circle.distance(&p(0.0), &p(24.0)));
+        assert_eq!(22.0, circle.clockwise(&p(1.0), &p(23.0)));
+        assert_eq!(p(22.0), circle.move_by(&p(1.0), -3.0));
+    }
+
+    #[test]
+    fn adjacent_servers_wrap_around() {
+        let circle = Circle::new(100.0);
+        let config = ServerConfiguration::new(vec![
+            circle.point(10.0),
+            circle.point(50.0),
+            circle.point(70.0),
+        ]);
+        let adjacent = |x| circle.adjacent_servers(&config, &Request::Simple(circle.point(x)));
+        assert_eq!((2, 0), adjacent(5.0));
+        assert_eq!((2, 0), adjacent(80.0));
+        assert_eq!((1, 1), adjacent(50.0));
+    }
+
+    #[test]
+    fn servers_keep_index_across_zero() {
+        let circle = Circle::new(100.0);
+        let instance = Instance::with_metric(
+            circle,
+            vec![Request::Simple(circle.point(5.0))],
+            ServerConfiguration::new(vec![circle.point(40.0), circle.point(95.0)]),
+        );
+        let pred = Prediction::from(vec![1]);
+        let schedule = pred.to_schedule(&instance);
+        assert_eq!(circle.point(5.0), schedule.last().unwrap()[1]);
+        assert_eq!(10.0, schedule.metric_cost(&circle));
+        assert_eq!(
+            vec![1],
+            schedule
+                .to_prediction(&instance)
+                .into_iter()
+                .collect::<Vec<usize>>()
+        );
+    }
+}
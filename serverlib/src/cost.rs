@@ -1,5 +1,10 @@
+use crate::error::Error;
+use crate::instance::Instance;
+use crate::metric::Metric;
+use crate::request::Request;
 use crate::server_config::ServerConfiguration;
 use crate::schedule::Schedule;
+use std::str::FromStr;
 
 pub trait CostMetric<T> {
     fn diff(&self, other: &Self) -> T;
@@ -7,23 +12,77 @@ pub trait CostMetric<T> {
 
 impl CostMetric<f64> for ServerConfiguration {
     fn diff(&self, other: &ServerConfiguration) -> f64 {
-        return self
+        self
             .into_iter()
-            .zip(other.into_iter())
+            .zip(other)
             .map(|(a, b)| (a - b).abs() as f64)
-            .sum::<f64>();
+            .sum::<f64>()
     }
 }
 
 impl CostMetric<f64> for Schedule {
+    /// Panics if the schedules have different lengths, see `Schedule::try_diff`.
     fn diff(&self, other: &Self) -> f64 {
+        self.try_diff(other).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl Schedule {
+    pub fn try_diff(&self, other: &Self) -> Result<f64, Error> {
         if self.len() != other.len() {
-            panic!("Schedules must have same size!")
+            return Err(Error::LengthMismatch {
+                left: self.len(),
+                right: other.len(),
+            });
         }
-        return self
+        Ok(self
             .into_iter()
-            .zip(other.into_iter())
-            .map(|(c1, c2)| c1.diff(c2) as f64)
-            .sum();
+            .zip(other)
+            .map(|(c1, c2)| c1.diff(c2))
+            .sum())
     }
-}
\ No newline at end of file
+}
+
+/// How relocation requests `s -> t` of the k-taxi problem are charged.
+///
+/// In the easy k-taxi problem, only moving to `s` costs, whereas the hard k-taxi problem also
+/// charges the relocation from `s` to `t`. Since every schedule has to relocate, both models differ
+/// by the same constant for all schedules of an instance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CostModel {
+    #[default]
+    Easy,
+    Hard,
+}
+
+impl FromStr for CostModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(CostModel::Easy),
+            "hard" => Ok(CostModel::Hard),
+            _ => Err(format!("Unknown cost model {}, expected easy or hard", s)),
+        }
+    }
+}
+
+impl CostModel {
+    /// The cost of relocating the serving server from `s` to `t`.
+    pub fn relocation_cost<M: Metric>(&self, metric: &M, req: &Request<M::Point>) -> f64 {
+        match self {
+            CostModel::Easy => 0.0,
+            CostModel::Hard => metric.distance(req.pos(), req.end()) as f64,
+        }
+    }
+
+    /// The total relocation cost of all requests of an instance.
+    pub fn relocation_costs<M: Metric>(&self, instance: &Instance<M>) -> f64 {
+        instance
+            .requests()
+            .iter()
+            .map(|req| self.relocation_cost(instance.metric(), req))
+            .sum()
+    }
+}
@@ -0,0 +1,66 @@
+use std::fmt;
+
+/// Errors of the fallible `try_*` counterparts of methods which panic otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// Two schedules of different lengths are compared.
+    LengthMismatch { left: usize, right: usize },
+    /// A move is appended to a schedule without an initial configuration.
+    EmptySchedule,
+    /// A configuration has no servers.
+    NoServers,
+    /// A server index exceeds the number of servers.
+    InvalidServer { server: usize, k: usize },
+    /// No server is located at the end of the request served in the given step.
+    Uncovered { step: usize, request: String },
+    /// A schedule does not serve an instance, where `step` is the infeasible step if it is known.
+    Infeasible { step: Option<usize>, msg: String },
+    /// Not all bins of predictions could be filled.
+    MissingPredictions(String),
+    /// The edges do not form a tree.
+    InvalidTree(String),
+    /// There is no algorithm with this name.
+    UnknownAlgorithm(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::LengthMismatch { left, right } => write!(
+                f,
+                "Schedules must have the same length, but have {} and {}!",
+                left, right
+            ),
+            Error::EmptySchedule => write!(f, "The schedule has no initial configuration!"),
+            Error::NoServers => write!(f, "The configuration has no servers!"),
+            Error::InvalidServer { server, k } => {
+                write!(f, "There is no server {} among {} servers!", server, k)
+            }
+            Error::Uncovered { step, request } => {
+                write!(f, "Request {} is not served in step {}!", request, step)
+            }
+            Error::Infeasible {
+                step: Some(step),
+                msg,
+            } => write!(f, "Infeasible schedule in step {}: {}!", step, msg),
+            Error::Infeasible { step: None, msg } => write!(f, "Infeasible schedule: {}!", msg),
+            Error::MissingPredictions(msg) => {
+                write!(f, "Not all required predictions have been found: {}!", msg)
+            }
+            Error::InvalidTree(msg) => write!(f, "Invalid tree: {}!", msg),
+            Error::UnknownAlgorithm(name) => write!(f, "Unknown algorithm {}!", name),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Error {
+    /// The infeasible step of a schedule, where step `0` is the initial configuration.
+    pub fn step(&self) -> Option<usize> {
+        match self {
+            Error::Infeasible { step, .. } => *step,
+            _ => None,
+        }
+    }
+}
@@ -1,46 +1,71 @@
+use crate::metric::{Line, Metric};
 use crate::request::*;
 use crate::server_config::ServerConfiguration;
 
-/// Represents an instance for an online server problem on the line.
+/// Represents an instance for an online server problem in a metric space.
 ///
-/// An instance is composed of an `initial_configuration` and a sequence of `requests`.
+/// An instance is composed of an `initial_configuration` and a sequence of `requests` in a `metric` space.
+/// If not stated otherwise, the metric space is the line.
 ///
 /// ## Examples
 ///
 /// An instance with initial configuration (3,6) and requests 2,8,5 can be directly build from vectors using the `From`-trait:
 /// ```
-/// # use serversim::instance::Instance;
+/// # use serverlib::instance::Instance;
 /// let instance = Instance::from((vec![2,8,5], vec![3,6]));
 /// assert_eq!(2, instance.k());
 /// assert_eq!(3, instance.length());
 /// ```
 #[derive(Clone, Debug)]
-pub struct Instance {
-    requests: Vec<Request>,
-    initial_positions: ServerConfiguration,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "M: serde::Serialize, M::Point: serde::Serialize",
+        deserialize = "M: serde::Deserialize<'de>, M::Point: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Instance<M: Metric = Line> {
+    requests: Vec<Request<M::Point>>,
+    initial_positions: ServerConfiguration<M::Point>,
+    metric: M,
 }
 
 impl Instance {
     pub fn new(requests: Vec<Request>, initial_positions: ServerConfiguration) -> Instance {
+        Instance::with_metric(Line, requests, initial_positions)
+    }
+}
+
+impl<M: Metric> Instance<M> {
+    pub fn with_metric(
+        metric: M,
+        requests: Vec<Request<M::Point>>,
+        initial_positions: ServerConfiguration<M::Point>,
+    ) -> Instance<M> {
         Instance {
-            requests: requests,
-            initial_positions: initial_positions,
+            requests,
+            initial_positions,
+            metric,
         }
     }
+    pub fn metric(&self) -> &M {
+        &self.metric
+    }
     pub fn length(&self) -> usize {
         self.requests.len()
     }
     pub fn k(&self) -> usize {
         self.initial_positions.size()
     }
-    pub fn requests(&self) -> &[Request] {
+    pub fn requests(&self) -> &[Request<M::Point>] {
         &self.requests
     }
-    pub fn initial_positions(&self) -> &ServerConfiguration {
+    pub fn initial_positions(&self) -> &ServerConfiguration<M::Point> {
         &self.initial_positions
     }
-    pub fn req(&self, index: &usize) -> Request {
-        return self.requests[*index];
+    pub fn req(&self, index: &usize) -> Request<M::Point> {
+        self.requests[*index]
     }
 
     pub fn is_taxi_instance(&self) -> bool {
@@ -48,8 +73,8 @@ impl Instance {
     }
 }
 
-impl std::iter::IntoIterator for Instance {
-    type Item = Request;
+impl<M: Metric> std::iter::IntoIterator for Instance<M> {
+    type Item = Request<M::Point>;
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
@@ -57,14 +82,14 @@ impl std::iter::IntoIterator for Instance {
     }
 }
 
-impl std::ops::Index<usize> for Instance {
-    type Output = Request;
+impl<M: Metric> std::ops::Index<usize> for Instance<M> {
+    type Output = Request<M::Point>;
     fn index(&self, idx: usize) -> &Self::Output {
         &self.requests[idx]
     }
 }
 
-impl std::fmt::Display for Instance {
+impl<M: Metric> std::fmt::Display for Instance<M> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             f,
@@ -110,7 +135,7 @@ mod tests {
     use super::*;
 
     fn get_instance() -> Instance {
-        return Instance::from((vec![1, 3, 6, 9], vec![5, 5]));
+        Instance::from((vec![1, 3, 6, 9], vec![5, 5]))
     }
 
     #[test]
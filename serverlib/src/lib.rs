@@ -1,16 +1,27 @@
-
+pub mod circle;
 pub mod cost;
+pub mod error;
 pub mod instance;
+pub mod metric;
+pub mod plane;
 pub mod pred;
 pub mod request;
 pub mod schedule;
 pub mod server_config;
+pub mod svg;
+pub mod tree;
+
+pub use crate::error::Error;
 
 pub mod prelude {
-    pub use crate::cost::CostMetric;
+    pub use crate::circle::{Circle, CirclePoint};
+    pub use crate::cost::{CostMetric, CostModel};
     pub use crate::instance::Instance;
-    pub use crate::schedule::Schedule;
-    pub use crate::pred::{Prediction, PredictionError};
+    pub use crate::metric::{Line, Metric, Point};
+    pub use crate::plane::{Plane, PlanePoint};
+    pub use crate::pred::Prediction;
     pub use crate::request::Request;
+    pub use crate::schedule::{Schedule, ValidationReport};
     pub use crate::server_config::ServerConfiguration;
-}
\ No newline at end of file
+    pub use crate::tree::{Tree, TreePoint};
+}
@@ -0,0 +1,142 @@
+use crate::server_config::ServerConfiguration;
+use std::fmt::{Debug, Display};
+
+/// Represents a point of a metric space at which servers and requests can be located.
+///
+/// Server configurations are stored in a canonical order, which is defined by `normalize`. Points
+/// without a natural order keep the order of the servers, such that the `i`-th position of two
+/// configurations always belongs to the same server.
+pub trait Point: Copy + Debug + PartialEq + Display {
+    fn normalize(_positions: &mut [Self]) {}
+}
+
+/// Points on the line are ordered from left to right.
+impl Point for f32 {
+    fn normalize(positions: &mut [f32]) {
+        positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    }
+}
+
+/// Represents a metric space for online server problems.
+///
+/// ## Examples
+///
+/// The line is the default metric of all instances:
+/// ```
+/// # use serverlib::metric::{Line, Metric};
+/// # use serverlib::server_config::ServerConfiguration;
+/// assert_eq!(3.0, Line.distance(&2.0, &5.0));
+/// let config1 = ServerConfiguration::from(vec![1, 4]);
+/// let config2 = ServerConfiguration::from(vec![2, 7]);
+/// assert_eq!(4.0, Line.config_distance(&config1, &config2));
+/// ```
+pub trait Metric: Clone + Debug {
+    type Point: Point;
+
+    fn distance(&self, a: &Self::Point, b: &Self::Point) -> f32;
+
+    /// The cost of moving the servers from configuration `a` to configuration `b`.
+    fn config_distance(
+        &self,
+        a: &ServerConfiguration<Self::Point>,
+        b: &ServerConfiguration<Self::Point>,
+    ) -> f64 {
+        a.into_iter()
+            .zip(b)
+            .map(|(x, y)| self.distance(x, y) as f64)
+            .sum()
+    }
+}
+
+/// The real line.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Line;
+
+impl Metric for Line {
+    type Point = f32;
+
+    fn distance(&self, a: &f32, b: &f32) -> f32 {
+        (a - b).abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::Instance;
+    use crate::pred::Prediction;
+    use crate::request::Request;
+    use crate::schedule::Schedule;
+
+    /// Points of the uniform metric, where all distinct points have distance one.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Label(char);
+
+    impl std::fmt::Display for Label {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl Point for Label {}
+
+    #[derive(Clone, Debug)]
+    struct Uniform;
+
+    impl Metric for Uniform {
+        type Point = Label;
+
+        fn distance(&self, a: &Label, b: &Label) -> f32 {
+            if a == b {
+                0.0
+            } else {
+                1.0
+            }
+        }
+    }
+
+    fn get_instance() -> Instance<Uniform> {
+        let requests = "bcbd".chars().map(|c| Request::Simple(Label(c))).collect();
+        let initial = ServerConfiguration::new(vec![Label('a'), Label('b')]);
+        Instance::with_metric(Uniform, requests, initial)
+    }
+
+    #[test]
+    fn line_is_default_metric() {
+        let instance = Instance::from((vec![1, 3], vec![0, 5]));
+        assert_eq!(2.0, instance.metric().distance(&1.0, &3.0));
+    }
+
+    #[test]
+    fn configurations_keep_server_order() {
+        let config = ServerConfiguration::new(vec![Label('b'), Label('a')]);
+        assert_eq!(Label('b'), config[0]);
+        let next = config.from_move(1, Label('c'));
+        assert_eq!(Label('b'), next[0]);
+        assert_eq!(Label('c'), next[1]);
+    }
+
+    #[test]
+    fn schedule_metric_cost_works() {
+        let instance = get_instance();
+        let pred = Prediction::from(vec![0, 0, 1, 0]);
+        let schedule = pred.to_schedule(&instance);
+        assert_eq!(3.0, schedule.metric_cost(instance.metric()));
+        assert_eq!(
+            pred.into_iter().collect::<Vec<usize>>(),
+            schedule
+                .to_prediction(&instance)
+                .into_iter()
+                .collect::<Vec<usize>>()
+        );
+    }
+
+    #[test]
+    fn eta_uses_metric() {
+        let instance = get_instance();
+        let solution: Schedule<Label> = Prediction::from(vec![1, 0, 1, 0]).to_schedule(&instance);
+        let pred = Prediction::from(vec![0, 0, 1, 0]);
+        assert_eq!(1.0, pred.eta(&solution, &instance));
+    }
+}
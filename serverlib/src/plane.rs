@@ -0,0 +1,81 @@
+use crate::metric::{Metric, Point};
+use std::fmt;
+
+/// A point in the Euclidean plane.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlanePoint {
+    x: f32,
+    y: f32,
+}
+
+impl PlanePoint {
+    pub fn new(x: f32, y: f32) -> PlanePoint {
+        PlanePoint { x, y }
+    }
+
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+}
+
+impl From<(f32, f32)> for PlanePoint {
+    fn from((x, y): (f32, f32)) -> PlanePoint {
+        PlanePoint::new(x, y)
+    }
+}
+
+impl From<(i32, i32)> for PlanePoint {
+    fn from((x, y): (i32, i32)) -> PlanePoint {
+        PlanePoint::new(x as f32, y as f32)
+    }
+}
+
+impl fmt::Display for PlanePoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// Points of the plane have no natural order, so servers keep their index.
+impl Point for PlanePoint {}
+
+/// The two-dimensional Euclidean plane.
+///
+/// ## Examples
+///
+/// ```
+/// # use serverlib::metric::Metric;
+/// # use serverlib::plane::{Plane, PlanePoint};
+/// assert_eq!(5.0, Plane.distance(&PlanePoint::from((1, 1)), &PlanePoint::from((4, 5))));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plane;
+
+impl Metric for Plane {
+    type Point = PlanePoint;
+
+    fn distance(&self, a: &PlanePoint, b: &PlanePoint) -> f32 {
+        (a.x - b.x).hypot(a.y - b.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server_config::ServerConfiguration;
+
+    #[test]
+    fn plane_config_distance_works() {
+        let a = ServerConfiguration::new(vec![PlanePoint::from((3, 0)), PlanePoint::from((0, 0))]);
+        let b = ServerConfiguration::new(vec![PlanePoint::from((0, 4)), PlanePoint::from((1, 0))]);
+        // servers are not reordered, so the first server moves from (3, 0) to (0, 4)
+        assert_eq!(PlanePoint::from((3, 0)), a[0]);
+        assert_eq!(6.0, Plane.config_distance(&a, &b));
+    }
+}
@@ -1,41 +1,11 @@
+use crate::error::Error;
 use crate::instance::Instance;
+use crate::metric::Metric;
 use crate::schedule::Schedule;
-use std::error::Error;
-use std::fmt;
 use std::iter::FromIterator;
 
-#[derive(Debug, Clone)]
-pub struct PredictionError {
-    msg: String,
-}
-
-impl fmt::Display for PredictionError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "Not all required predictions have been found: {}!",
-            self.msg
-        )
-    }
-}
-
-impl Error for PredictionError {
-    fn description(&self) -> &str {
-        "Not all required predictions have been found!"
-    }
-
-    fn cause(&self) -> Option<&dyn Error> {
-        None
-    }
-}
-
-impl PredictionError {
-    pub fn new(msg: String) -> PredictionError {
-        PredictionError { msg: msg }
-    }
-}
-
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Prediction(Vec<usize>);
 
 impl From<Vec<usize>> for Prediction {
@@ -76,22 +46,42 @@ impl std::ops::Index<usize> for Prediction {
 }
 
 impl Prediction {
-    pub fn to_schedule(&self, instance: &Instance) -> Schedule {
+    /// The schedule which serves each request with the predicted server.
+    ///
+    /// Panics if a predicted server does not exist, see `try_to_schedule`.
+    pub fn to_schedule<M: Metric>(&self, instance: &Instance<M>) -> Schedule<M::Point> {
+        self.try_to_schedule(instance)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_to_schedule<M: Metric>(
+        &self,
+        instance: &Instance<M>,
+    ) -> Result<Schedule<M::Point>, Error> {
         let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
 
         for (idx, req) in instance.requests().iter().enumerate() {
-            schedule.append_move(self[idx], req.distance_to(&0.0));
+            schedule.try_append_move(self[idx], *req.end())?;
         }
-        //schedule.normalize();
-        schedule
+        Ok(schedule)
+    }
+
+    /// The additional cost of following the prediction compared to the optimal `solution`.
+    pub fn eta<M: Metric>(&self, solution: &Schedule<M::Point>, instance: &Instance<M>) -> f64 {
+        self.try_eta(solution, instance)
+            .unwrap_or_else(|e| panic!("{}", e))
     }
 
-    pub fn eta(&self, solution: &Schedule, instance: &Instance) -> f64 {
-        let pred_schedule = self.to_schedule(instance);
-        return pred_schedule.cost() - solution.cost();
+    pub fn try_eta<M: Metric>(
+        &self,
+        solution: &Schedule<M::Point>,
+        instance: &Instance<M>,
+    ) -> Result<f64, Error> {
+        let pred_schedule = self.try_to_schedule(instance)?;
+        Ok(pred_schedule.metric_cost(instance.metric()) - solution.metric_cost(instance.metric()))
     }
 
     pub fn predicted_server(&self, request_index: usize) -> usize {
-        return self[request_index];
+        self[request_index]
     }
 }
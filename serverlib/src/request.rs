@@ -1,3 +1,5 @@
+use crate::metric::Point;
+
 /// Represents a request for a server problem on the line.
 ///
 /// A request consists of two parts: `s` and `t`. If `s==t`, the requests is called simple, other wise relocating.
@@ -8,52 +10,56 @@
 ///
 /// Requests can directly be derived from single integers or tuples:
 /// ```
-/// # use serversim::request::Request;
+/// # use serverlib::request::Request;
 /// let simple_req = Request::from(4);
 /// assert_eq!(Request::Simple(4.0), simple_req);
 /// let relocation_req = Request::from((2,4));
 /// assert_eq!(Request::Relocation(2.0, 4.0), relocation_req);
 /// ```
+/// Requests are generic over the point type of the underlying metric space, where the line is the default.
 #[derive(Copy, Clone, Debug, PartialEq)]
-pub enum Request {
-    Simple(f32),
-    Relocation(f32, f32),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Request<P = f32> {
+    Simple(P),
+    Relocation(P, P),
 }
 
-impl Request {
-    pub fn pos(&self) -> &f32 {
+impl<P: Point> Request<P> {
+    /// The point `s` where the request has to be served.
+    pub fn pos(&self) -> &P {
+        match self {
+            Request::Simple(x) => x,
+            Request::Relocation(x, _) => x,
+        }
+    }
+
+    /// The point `t` where the serving server ends up.
+    pub fn end(&self) -> &P {
         match self {
             Request::Simple(x) => x,
-            Request::Relocation(x,_) => x,
+            Request::Relocation(_, y) => y,
         }
     }
 
     pub fn is_simple(&self) -> bool {
         matches!(*self, Request::Simple(_))
     }
+}
 
+impl Request {
+    /// The distance from the end `t` to `other`, i.e., the relocation from `s` to `t` is not included (see `CostModel`).
     pub fn distance_to(&self, other: &f32) -> f32 {
-        match self {
-            Request::Simple(x) => (x - other).abs(),
-            Request::Relocation(_, y) => (y - other).abs(),
-        }
+        (self.end() - other).abs()
     }
     pub fn distance_to_req(&self, other: &Request) -> f32 {
-        let pos = other.distance_from(&0.0);
-        match self {
-            Request::Simple(x) => (x - pos).abs(),
-            Request::Relocation(_, y) => (y - pos).abs(),
-        }
+        (self.end() - other.pos()).abs()
     }
     pub fn distance_from(&self, other: &f32) -> f32 {
-        match self {
-            Request::Simple(x) => (x - other).abs(),
-            Request::Relocation(x, _) => (x - other).abs(),
-        }
+        (self.pos() - other).abs()
     }
 }
 
-impl std::fmt::Display for Request {
+impl<P: Point> std::fmt::Display for Request<P> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Request::Simple(x) => write!(f, "{}", x),
@@ -1,48 +1,209 @@
-use crate::cost::CostMetric;
+use crate::cost::{CostMetric, CostModel};
+use crate::error::Error as ServerError;
 use crate::instance::Instance;
+use crate::metric::{Metric, Point};
 use crate::pred::Prediction;
 use crate::server_config::ServerConfiguration;
 
+/// Tolerance for servers covering a request, since positions drift due to floating point arithmetic.
+pub const TOLERANCE: f32 = 1e-3;
+
+/// A summary of a feasible schedule.
 #[derive(Debug, Clone, PartialEq)]
-pub struct Schedule(Vec<ServerConfiguration>);
+pub struct ValidationReport {
+    /// The number of served requests.
+    pub steps: usize,
+    pub k: usize,
+    /// The largest distance between a request and the server covering it.
+    pub max_drift: f32,
+}
 
-impl Schedule {
-    pub fn empty() -> Schedule {
+/// A sequence of server configurations, starting with the initial configuration.
+///
+/// Schedules are generic over the point type of the underlying metric space, where the line is the default.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Schedule<P = f32>(Vec<ServerConfiguration<P>>);
+
+impl<P: Point> Schedule<P> {
+    pub fn empty() -> Schedule<P> {
         Schedule(Vec::new())
     }
 
-    pub fn with_initial_config(initial_config: ServerConfiguration) -> Self {
+    pub fn with_initial_config(initial_config: ServerConfiguration<P>) -> Self {
         Schedule(vec![initial_config])
     }
 
-    pub fn append_config(&mut self, config: ServerConfiguration) {
+    pub fn append_config(&mut self, config: ServerConfiguration<P>) {
         self.0.push(config);
     }
 
-    pub fn append_move(&mut self, id: usize, position: f32) {
-        match self.0.last() {
-            None => println!("Cannot append move as there is no initial configuration!"),
-            Some(config) => {
-                let next_conf = config.from_move(id, position);
-                self.0.push(next_conf);
-            }
+    /// Appends the configuration after moving server `id`.
+    ///
+    /// Panics if there is no initial configuration or no such server, see `try_append_move`.
+    pub fn append_move(&mut self, id: usize, position: P) {
+        self.try_append_move(id, position)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_append_move(&mut self, id: usize, position: P) -> Result<(), ServerError> {
+        let config = self.0.last().ok_or(ServerError::EmptySchedule)?;
+        if id >= config.size() {
+            return Err(ServerError::InvalidServer {
+                server: id,
+                k: config.size(),
+            });
         }
+        let next_conf = config.from_move(id, position);
+        self.0.push(next_conf);
+        Ok(())
     }
 
     pub fn len(&self) -> usize {
         self.0.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     pub fn normalize(&mut self) {
         for config in self {
             config.normalize();
         }
     }
 
-    pub fn last(&self) -> Option<&ServerConfiguration> {
+    pub fn last(&self) -> Option<&ServerConfiguration<P>> {
         self.0.last()
     }
 
+    /// The cost of this schedule in the given metric space.
+    pub fn metric_cost<M: Metric<Point = P>>(&self, metric: &M) -> f64 {
+        self.0
+            .iter()
+            .zip(self.0.iter().skip(1))
+            .map(|(from, to)| metric.config_distance(from, to))
+            .sum()
+    }
+
+    /// Checks that this schedule starts in the initial configuration of `instance` and serves all of its requests.
+    ///
+    /// Every configuration must consist of `k` servers, and after the `i`-th step a server must be located at the
+    /// end of the `i`-th request (up to `TOLERANCE`).
+    pub fn validate<M: Metric<Point = P>>(
+        &self,
+        instance: &Instance<M>,
+    ) -> Result<ValidationReport, ServerError> {
+        if self.len() != instance.length() + 1 {
+            return Err(ServerError::Infeasible {
+                step: None,
+                msg: format!(
+                    "{} configurations for {} requests",
+                    self.len(),
+                    instance.length()
+                ),
+            });
+        }
+        for (step, config) in self.0.iter().enumerate() {
+            if config.size() != instance.k() {
+                return Err(ServerError::Infeasible {
+                    step: Some(step),
+                    msg: format!("{} servers instead of {}", config.size(), instance.k()),
+                });
+            }
+        }
+        let mut initial = instance.initial_positions().clone();
+        initial.normalize();
+        let mut first = self.0[0].clone();
+        first.normalize();
+        if instance.metric().config_distance(&initial, &first) > TOLERANCE as f64 {
+            return Err(ServerError::Infeasible {
+                step: Some(0),
+                msg: "does not start in the initial configuration".to_string(),
+            });
+        }
+        let mut max_drift: f32 = 0.0;
+        for (i, (config, req)) in self.0.iter().skip(1).zip(instance.requests()).enumerate() {
+            let drift = config
+                .into_iter()
+                .map(|server| instance.metric().distance(server, req.end()))
+                .fold(f32::INFINITY, f32::min);
+            if drift > TOLERANCE {
+                return Err(ServerError::Infeasible {
+                    step: Some(i + 1),
+                    msg: format!("request {} is not covered", req),
+                });
+            }
+            max_drift = max_drift.max(drift);
+        }
+        Ok(ValidationReport {
+            steps: instance.length(),
+            k: instance.k(),
+            max_drift,
+        })
+    }
+
+    /// Panics if a request is not served, see `try_to_prediction`.
+    pub fn to_prediction<M: Metric<Point = P>>(&self, instance: &Instance<M>) -> Prediction {
+        self.try_to_prediction(instance)
+            .unwrap_or_else(|e| panic!("{}\nSolution={:?} Instance={}", e, self, instance))
+    }
+
+    /// The servers located at the end of each request after serving it.
+    pub fn try_to_prediction<M: Metric<Point = P>>(
+        &self,
+        instance: &Instance<M>,
+    ) -> Result<Prediction, ServerError> {
+        self.into_iter()
+            .skip(1)
+            .enumerate()
+            .map(|(idx, config)| serving_server(instance, idx, config))
+            .collect()
+    }
+
+    /// Panics if a request is not served, see `try_to_lazy`.
+    pub fn to_lazy<M: Metric<Point = P>>(self, instance: &Instance<M>) -> Schedule<P> {
+        self.try_to_lazy(instance)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// The schedule which only moves the serving server to each request.
+    pub fn try_to_lazy<M: Metric<Point = P>>(
+        mut self,
+        instance: &Instance<M>,
+    ) -> Result<Schedule<P>, ServerError> {
+        if self.is_empty() {
+            return Err(ServerError::EmptySchedule);
+        }
+        let mut lazy_schedule = Schedule::empty();
+        let initial = self.0.remove(0);
+        lazy_schedule.append_config(initial);
+
+        for (idx, config) in self.into_iter().enumerate() {
+            let moved_server = serving_server(instance, idx, &config)?;
+            lazy_schedule.try_append_move(moved_server, *instance[idx].pos())?;
+        }
+
+        Ok(lazy_schedule)
+    }
+}
+
+/// The server located at the end of the request served in step `idx`.
+fn serving_server<M: Metric>(
+    instance: &Instance<M>,
+    idx: usize,
+    config: &ServerConfiguration<M::Point>,
+) -> Result<usize, ServerError> {
+    config
+        .into_iter()
+        .position(|server| instance.metric().distance(instance[idx].end(), server) == 0.0)
+        .ok_or_else(|| ServerError::Uncovered {
+            step: idx + 1,
+            request: instance[idx].to_string(),
+        })
+}
+
+impl Schedule {
     pub fn cost(&self) -> f64 {
         if self.len() <= 1 {
             0.0
@@ -60,43 +221,56 @@ impl Schedule {
         }
     }
 
-    pub fn to_prediction(&self, instance: &Instance) -> Prediction {
-        self
-        .into_iter()
-        .skip(1)
-        .enumerate()
-        .map(|(idx, config)| {
-            config
-            .into_iter()
-            .enumerate()
-            .find(|(_, server)| instance[idx].distance_to(server) == 0.0)
-            .map(|(i, _)| i)
-            .unwrap_or_else(|| panic!("Cannot find predicted server. Please investigate!\nSolution={:?} Instance={}", self, instance))
-        })
-        .collect::<Prediction>()
+    /// The cost of this schedule for the k-taxi problem, where relocations are charged according to `model`.
+    ///
+    /// In contrast to `cost`, the server serving a request first has to move to its start `s` before it
+    /// is relocated to its end `t`. Since configurations are sorted, the cheapest choice of the serving
+    /// server is assumed.
+    ///
+    /// Panics if a request is not served, see `try_taxi_cost`.
+    pub fn taxi_cost(&self, instance: &Instance, model: CostModel) -> f64 {
+        self.try_taxi_cost(instance, model)
+            .unwrap_or_else(|e| panic!("{}", e))
     }
 
-    pub fn to_lazy(mut self, instance: &Instance) -> Schedule {
-        let mut lazy_schedule = Schedule::empty();
-        let initial = self.0.remove(0);
-        lazy_schedule.append_config(initial);
-
-        for (idx, config) in self.into_iter().enumerate() {
-            let moved_server = config
-                .into_iter()
-                .enumerate()
-                .find(|(_, server)| instance[idx].distance_to(server) == 0.0)
-                .map(|(i, _)| i)
-                .unwrap();
-            lazy_schedule.append_move(moved_server, *instance[idx].pos())
-        }
+    /// Like `taxi_cost`, but fails if no server is located at the end of a request after serving it.
+    pub fn try_taxi_cost(&self, instance: &Instance, model: CostModel) -> Result<f64, ServerError> {
+        self.0
+            .iter()
+            .zip(self.0.iter().skip(1))
+            .zip(instance.requests())
+            .enumerate()
+            .map(|(idx, ((from, to), req))| {
+                let served = to.into_iter().position(|s| s == req.end()).ok_or_else(|| {
+                    ServerError::Uncovered {
+                        step: idx + 1,
+                        request: req.to_string(),
+                    }
+                })?;
+                let others = |config: &ServerConfiguration, i: usize| {
+                    let mut positions: Vec<f32> = config.into_iter().copied().collect();
+                    positions.remove(i);
+                    ServerConfiguration::new(positions)
+                };
+                let rest = others(to, served);
+                Ok((0..from.size())
+                    .map(|i| req.distance_from(&from[i]) as f64 + others(from, i).diff(&rest))
+                    .fold(f64::INFINITY, f64::min)
+                    + model.relocation_cost(instance.metric(), req))
+            })
+            .sum()
+    }
+}
 
-        lazy_schedule
+impl<P> std::ops::Index<usize> for Schedule<P> {
+    type Output = ServerConfiguration<P>;
+    fn index(&self, idx: usize) -> &Self::Output {
+        &self.0[idx]
     }
 }
 
-impl std::iter::IntoIterator for Schedule {
-    type Item = ServerConfiguration;
+impl<P> std::iter::IntoIterator for Schedule<P> {
+    type Item = ServerConfiguration<P>;
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
@@ -104,26 +278,26 @@ impl std::iter::IntoIterator for Schedule {
     }
 }
 
-impl<'a> std::iter::IntoIterator for &'a Schedule {
-    type Item = &'a ServerConfiguration;
-    type IntoIter = std::slice::Iter<'a, ServerConfiguration>;
+impl<'a, P> std::iter::IntoIterator for &'a Schedule<P> {
+    type Item = &'a ServerConfiguration<P>;
+    type IntoIter = std::slice::Iter<'a, ServerConfiguration<P>>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.iter()
     }
 }
 
-impl<'a> std::iter::IntoIterator for &'a mut Schedule {
-    type Item = &'a mut ServerConfiguration;
-    type IntoIter = std::slice::IterMut<'a, ServerConfiguration>;
+impl<'a, P> std::iter::IntoIterator for &'a mut Schedule<P> {
+    type Item = &'a mut ServerConfiguration<P>;
+    type IntoIter = std::slice::IterMut<'a, ServerConfiguration<P>>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.iter_mut()
     }
 }
 
-impl std::iter::FromIterator<ServerConfiguration> for Schedule {
-    fn from_iter<I: IntoIterator<Item = ServerConfiguration>>(iter: I) -> Self {
+impl<P: Point> std::iter::FromIterator<ServerConfiguration<P>> for Schedule<P> {
+    fn from_iter<I: IntoIterator<Item = ServerConfiguration<P>>>(iter: I) -> Self {
         let mut c = Schedule::empty();
         for i in iter {
             c.append_config(i);
@@ -132,8 +306,8 @@ impl std::iter::FromIterator<ServerConfiguration> for Schedule {
     }
 }
 
-impl From<Vec<ServerConfiguration>> for Schedule {
-    fn from(config_list: Vec<ServerConfiguration>) -> Self {
+impl<P: Point> From<Vec<ServerConfiguration<P>>> for Schedule<P> {
+    fn from(config_list: Vec<ServerConfiguration<P>>) -> Self {
         config_list.into_iter().collect()
     }
 }
@@ -142,7 +316,7 @@ impl From<Vec<Vec<i32>>> for Schedule {
     fn from(config_list: Vec<Vec<i32>>) -> Self {
         config_list
             .into_iter()
-            .map(|vec| ServerConfiguration::from(vec))
+            .map(ServerConfiguration::from)
             .collect()
     }
 }
@@ -151,6 +325,66 @@ impl From<Vec<Vec<i32>>> for Schedule {
 mod tests {
     use super::*;
 
+    #[test]
+    fn validate_works() {
+        let instance = Instance::from((vec![20, 80, 30], vec![50, 50]));
+        let schedule = Schedule::from(vec![vec![50, 50], vec![20, 50], vec![20, 80], vec![30, 80]]);
+        let report = schedule.validate(&instance).unwrap();
+        assert_eq!(3, report.steps);
+        assert_eq!(2, report.k);
+        assert_eq!(0.0, report.max_drift);
+
+        let uncovered =
+            Schedule::from(vec![vec![50, 50], vec![20, 50], vec![20, 70], vec![30, 70]]);
+        assert_eq!(Some(2), uncovered.validate(&instance).unwrap_err().step());
+
+        let too_short = Schedule::from(vec![vec![50, 50], vec![20, 50]]);
+        assert_eq!(None, too_short.validate(&instance).unwrap_err().step());
+
+        let wrong_k = Schedule::from(vec![vec![50, 50], vec![20, 50], vec![20, 80], vec![30]]);
+        assert_eq!(Some(3), wrong_k.validate(&instance).unwrap_err().step());
+
+        let wrong_start =
+            Schedule::from(vec![vec![40, 50], vec![20, 50], vec![20, 80], vec![30, 80]]);
+        assert_eq!(Some(0), wrong_start.validate(&instance).unwrap_err().step());
+
+        // small drift is tolerated
+        let mut drifted = Schedule::with_initial_config(ServerConfiguration::from(vec![50, 50]));
+        drifted.append_config(ServerConfiguration::new(vec![20.0001, 50.0]));
+        drifted.append_config(ServerConfiguration::new(vec![20.0001, 80.0]));
+        drifted.append_config(ServerConfiguration::new(vec![30.0, 80.0]));
+        assert!(drifted.validate(&instance).unwrap().max_drift > 0.0);
+    }
+
+    #[test]
+    fn taxi_cost_works() {
+        let instance = Instance::from((vec![(10, 30), (25, 0)], vec![0, 20]));
+        let schedule = Schedule::from(vec![vec![0, 20], vec![0, 30], vec![0, 0]]);
+        // the server at 20 moves to 10, then the server at 30 moves to 25 and gets relocated to 0
+        assert_eq!(10.0 + 5.0, schedule.taxi_cost(&instance, CostModel::Easy));
+        assert_eq!(
+            10.0 + 5.0 + 20.0 + 25.0,
+            schedule.taxi_cost(&instance, CostModel::Hard)
+        );
+        assert_eq!(45.0, CostModel::Hard.relocation_costs(&instance));
+
+        let instance = Instance::from((vec![20, 80], vec![50, 50]));
+        let schedule = Schedule::from(vec![vec![50, 50], vec![20, 50], vec![20, 80]]);
+        assert_eq!(
+            schedule.cost(),
+            schedule.taxi_cost(&instance, CostModel::Hard)
+        );
+
+        let schedule = Schedule::from(vec![vec![50, 50], vec![20, 50], vec![20, 70]]);
+        assert_eq!(
+            Err(ServerError::Uncovered {
+                step: 2,
+                request: instance[1].to_string()
+            }),
+            schedule.try_taxi_cost(&instance, CostModel::Easy)
+        );
+    }
+
     #[test]
     fn schedule_diff_works() {
         let conf11 = ServerConfiguration::from(vec![10, 15, 25]);
@@ -171,6 +405,35 @@ mod tests {
         let schedule2 = Schedule::from(vec![ServerConfiguration::from(vec![10])]);
         schedule1.diff(&schedule2);
     }
+    #[test]
+    fn try_methods_return_errors() {
+        let schedule1 = Schedule::from(vec![vec![10], vec![20]]);
+        let schedule2 = Schedule::from(vec![vec![10]]);
+        assert_eq!(
+            Err(ServerError::LengthMismatch { left: 2, right: 1 }),
+            schedule1.try_diff(&schedule2)
+        );
+
+        let mut empty: Schedule = Schedule::empty();
+        assert_eq!(
+            Err(ServerError::EmptySchedule),
+            empty.try_append_move(0, 10.0)
+        );
+        let mut schedule = Schedule::from(vec![vec![10, 20]]);
+        assert_eq!(
+            Err(ServerError::InvalidServer { server: 2, k: 2 }),
+            schedule.try_append_move(2, 10.0)
+        );
+
+        let instance = Instance::from((vec![20, 80], vec![50, 50]));
+        let uncovered = Schedule::from(vec![vec![50, 50], vec![20, 50], vec![20, 70]]);
+        assert!(matches!(
+            uncovered.try_to_prediction(&instance),
+            Err(ServerError::Uncovered { step: 2, .. })
+        ));
+        assert!(uncovered.try_to_lazy(&instance).is_err());
+    }
+
     #[test]
     fn append_move_works() {
         let mut schedule = Schedule::from(vec![ServerConfiguration::from(vec![10, 20])]);
@@ -181,6 +444,13 @@ mod tests {
         );
     }
 
+    #[test]
+    #[should_panic(expected = "There is no server 2 among 2 servers!")]
+    fn append_move_panics_for_invalid_server() {
+        let mut schedule = Schedule::from(vec![ServerConfiguration::from(vec![10, 20])]);
+        schedule.append_move(2, 30.0);
+    }
+
     #[test]
     fn test_lazy() {
         let instance = Instance::from((vec![1, 3, 6, 9], vec![0, 10]));
@@ -1,28 +1,31 @@
+use crate::error::Error;
+use crate::metric::Point;
 use crate::request::Request;
 
-/// Represents a state of the servers on the line.algorithm
+/// Represents a state of the servers in a metric space, where the line is the default.
 ///
 /// A configuration is a snapshot of the server's positions at a fixed point in time.
 /// For the k-server problem on the line, we assume that the configuration of the servers is ordered.
+/// In general, positions are brought into the canonical order of the point type, see `Point::normalize`.
 ///
 /// ## Examples
 ///
 /// ### Instantiation
 /// A server configuration implements the `From`-trait for a vector of integers:
 /// ```
-/// # use serversim::server_config::ServerConfiguration;
+/// # use serverlib::server_config::ServerConfiguration;
 /// let config: ServerConfiguration = vec![1,4,7].into();
 /// assert_eq!(3, config.size());
 /// ```
 /// It can also directly be build using `new`:
 /// ```
-/// # use serversim::server_config::ServerConfiguration;
+/// # use serverlib::server_config::ServerConfiguration;
 /// let config = ServerConfiguration::from(vec![1.0,4.0,7.0]);
 /// assert_eq!(3, config.size());
 /// ```
 /// Given a configuration, one can derive another configuration based on moving a server. These operations can also be chained.
 /// ```
-/// # use serversim::server_config::ServerConfiguration;
+/// # use serverlib::server_config::ServerConfiguration;
 /// let config = ServerConfiguration::from(vec![1,4,7]);
 /// let next = config.from_move(1, 6.0).from_move(2, 8.0);
 /// assert_eq!(ServerConfiguration::from(vec![1,6,8]), next);
@@ -31,7 +34,7 @@ use crate::request::Request;
 /// ### Normalization
 /// A server configuration can be sorted using the `normalize` method.
 /// ```
-/// # use serversim::server_config::ServerConfiguration;
+/// # use serverlib::server_config::ServerConfiguration;
 /// let mut config: ServerConfiguration = vec![3,5,1].into();
 /// config.normalize();
 /// assert_eq!(ServerConfiguration::from(vec![1,3,5]), config);
@@ -40,8 +43,8 @@ use crate::request::Request;
 /// ### Inspection
 /// Given a request, we can search the configuration for adjacent servers using the `adjacent_servers` method.
 /// ```
-/// # use serversim::server_config::ServerConfiguration;
-/// # use serversim::request::Request;
+/// # use serverlib::server_config::ServerConfiguration;
+/// # use serverlib::request::Request;
 /// let config: ServerConfiguration = vec![1,5,10].into();
 /// assert_eq!((Some(0), Some(1)), config.adjacent_servers(&Request::from(3)));
 /// assert_eq!((None, Some(0)), config.adjacent_servers(&Request::from(-1)));
@@ -50,42 +53,55 @@ use crate::request::Request;
 /// assert_eq!((Some(2), None), config.adjacent_servers(&Request::from(12)));
 /// ```
 #[derive(Debug, Clone, PartialEq)]
-pub struct ServerConfiguration(Vec<f32>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServerConfiguration<P = f32>(Vec<P>);
 
-impl ServerConfiguration {
-    pub fn new(mut positions: Vec<f32>) -> ServerConfiguration {
-        positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+impl<P: Point> ServerConfiguration<P> {
+    pub fn new(mut positions: Vec<P>) -> ServerConfiguration<P> {
+        P::normalize(&mut positions);
         ServerConfiguration(positions)
     }
 
-    pub fn from_move(&self, id: usize, pos: f32) -> ServerConfiguration {
+    pub fn from_move(&self, id: usize, pos: P) -> ServerConfiguration<P> {
         let mut new_pos = ServerConfiguration(self.0.to_vec());
         new_pos.0[id] = pos;
         new_pos.normalize();
-        return new_pos;
+        new_pos
     }
 
     pub fn normalize(&mut self) {
-        self.0.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        P::normalize(&mut self.0);
     }
 
     pub fn size(&self) -> usize {
         self.0.len()
     }
+}
 
+impl ServerConfiguration {
+    /// The servers adjacent to the request on the left and on the right.
+    ///
+    /// Panics if the configuration has no servers, see `try_adjacent_servers`.
     pub fn adjacent_servers(&self, req: &Request) -> (Option<usize>, Option<usize>) {
+        self.try_adjacent_servers(req).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_adjacent_servers(
+        &self,
+        req: &Request,
+    ) -> Result<(Option<usize>, Option<usize>), Error> {
+        if self.size() == 0 {
+            return Err(Error::NoServers);
+        }
         let mut right_index: Option<usize> = None;
-        let &pos = match req {
-            Request::Simple(x) => x,
-            Request::Relocation(x, _) => x,
-        };
+        let &pos = req.pos();
         for (idx, &server) in self.into_iter().enumerate() {
             if server >= pos {
                 right_index = Some(idx);
                 break;
             }
         }
-        match right_index {
+        Ok(match right_index {
             Some(0) => (None, right_index),
             Some(right) => {
                 if self[right] == pos {
@@ -97,7 +113,7 @@ impl ServerConfiguration {
                 }
             }
             None => (Some(self.size() - 1), None),
-        }
+        })
     }
 }
 
@@ -113,32 +129,32 @@ impl From<Vec<i32>> for ServerConfiguration {
     }
 }
 
-impl<'a> IntoIterator for &'a ServerConfiguration {
-    type Item = &'a f32;
-    type IntoIter = std::slice::Iter<'a, f32>;
+impl<'a, P> IntoIterator for &'a ServerConfiguration<P> {
+    type Item = &'a P;
+    type IntoIter = std::slice::Iter<'a, P>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.iter()
     }
 }
 
-impl IntoIterator for ServerConfiguration {
-    type Item = f32;
-    type IntoIter = std::vec::IntoIter<f32>;
+impl<P> IntoIterator for ServerConfiguration<P> {
+    type Item = P;
+    type IntoIter = std::vec::IntoIter<P>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.into_iter()
     }
 }
 
-impl std::ops::Index<usize> for ServerConfiguration {
-    type Output = f32;
+impl<P> std::ops::Index<usize> for ServerConfiguration<P> {
+    type Output = P;
     fn index(&self, idx: usize) -> &Self::Output {
         &self.0[idx]
     }
 }
 
-impl std::ops::IndexMut<usize> for ServerConfiguration {
+impl<P> std::ops::IndexMut<usize> for ServerConfiguration<P> {
     fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
         &mut self.0[idx]
     }
@@ -162,4 +178,13 @@ mod tests {
         let new_conf = config1.from_move(2, 30.0);
         assert_eq!(ServerConfiguration::from(vec![10, 15, 30]), new_conf);
     }
+
+    #[test]
+    fn try_adjacent_servers_works() {
+        let config: ServerConfiguration = vec![10, 20].into();
+        let req = Request::Simple(15.0);
+        assert_eq!(Ok((Some(0), Some(1))), config.try_adjacent_servers(&req));
+        let empty: ServerConfiguration = ServerConfiguration::new(vec![]);
+        assert_eq!(Err(Error::NoServers), empty.try_adjacent_servers(&req));
+    }
 }
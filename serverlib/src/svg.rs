@@ -0,0 +1,155 @@
+use crate::error::Error;
+use crate::instance::Instance;
+use crate::pred::Prediction;
+use crate::schedule::Schedule;
+use std::fmt::Write;
+
+const WIDTH: f32 = 800.0;
+const MARGIN: f32 = 40.0;
+const STEP_HEIGHT: f32 = 20.0;
+const COLORS: [&str; 6] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b",
+];
+
+/// Renders a space-time diagram of schedules on the line as SVG.
+///
+/// Time runs from top to bottom and positions from left to right. The servers of each schedule are
+/// drawn as polylines and the requests as dots. If a prediction is given, the predicted server is
+/// marked by a ring before each request and connected to it by a dashed line.
+///
+/// Every schedule has to contain the initial configuration and one configuration per request.
+pub fn render_svg(
+    instance: &Instance,
+    schedules: &[(&str, &Schedule)],
+    prediction: Option<&Prediction>,
+) -> Result<String, Error> {
+    for (_, schedule) in schedules {
+        if schedule.len() != instance.length() + 1 {
+            return Err(Error::LengthMismatch {
+                left: schedule.len(),
+                right: instance.length() + 1,
+            });
+        }
+    }
+    let predicted = prediction
+        .map(|pred| pred.try_to_schedule(instance).map(|s| (pred, s)))
+        .transpose()?;
+
+    let positions = instance
+        .requests()
+        .iter()
+        .flat_map(|req| vec![*req.pos(), *req.end()])
+        .chain(instance.initial_positions().into_iter().cloned())
+        .chain(
+            schedules
+                .iter()
+                .flat_map(|(_, s)| s.into_iter().flat_map(|c| c.into_iter().cloned())),
+        );
+    let (min, max) = positions.fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), x| {
+        (min.min(x), max.max(x))
+    });
+    let extent = if max > min { max - min } else { 1.0 };
+    let x = |pos: f32| MARGIN + (pos - min) / extent * (WIDTH - 2.0 * MARGIN);
+    let y = |step: usize| MARGIN + step as f32 * STEP_HEIGHT;
+    let height = 2.0 * MARGIN + instance.length() as f32 * STEP_HEIGHT;
+
+    let mut svg = String::new();
+    // writing into a string cannot fail
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+        WIDTH, height, WIDTH, height
+    );
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+
+    for (i, (name, schedule)) in schedules.iter().enumerate() {
+        let color = COLORS[i % COLORS.len()];
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" fill="{}" font-size="12">{}</text>"#,
+            MARGIN + i as f32 * 100.0,
+            MARGIN / 2.0,
+            color,
+            name
+        );
+        for server in 0..instance.k() {
+            let points: Vec<String> = schedule
+                .into_iter()
+                .enumerate()
+                .map(|(step, config)| format!("{:.1},{:.1}", x(config[server]), y(step)))
+                .collect();
+            let _ = writeln!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="1.5"/>"#,
+                points.join(" "),
+                color
+            );
+        }
+    }
+
+    if let Some((pred, pred_schedule)) = &predicted {
+        for (idx, req) in instance.requests().iter().enumerate() {
+            let server = pred_schedule[idx][pred[idx]];
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{:.1}" cy="{:.1}" r="5" fill="none" stroke="black"/>"#,
+                x(server),
+                y(idx)
+            );
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="black" stroke-dasharray="3,3"/>"#,
+                x(server),
+                y(idx),
+                x(*req.end()),
+                y(idx + 1)
+            );
+        }
+    }
+
+    for (idx, req) in instance.requests().iter().enumerate() {
+        if req.pos() != req.end() {
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="gray"/>"#,
+                x(*req.pos()),
+                y(idx + 1),
+                x(*req.end()),
+                y(idx + 1)
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="3" fill="black"/>"#,
+            x(*req.pos()),
+            y(idx + 1)
+        );
+    }
+    svg.push_str("</svg>\n");
+
+    Ok(svg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_svg_works() {
+        let instance = Instance::from((vec![20, 80, 30], vec![50, 50]));
+        let opt = Schedule::from(vec![vec![50, 50], vec![20, 50], vec![20, 80], vec![30, 80]]);
+        let pred = Prediction::from(vec![0, 1, 0]);
+        let svg = render_svg(&instance, &[("OPT", &opt)], Some(&pred)).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(2, svg.matches("<polyline").count());
+        // three requests and three predicted servers
+        assert_eq!(6, svg.matches("<circle").count());
+        assert_eq!(3, svg.matches("stroke-dasharray").count());
+
+        let too_short = Schedule::from(vec![vec![50, 50], vec![20, 50]]);
+        assert_eq!(
+            Err(Error::LengthMismatch { left: 2, right: 4 }),
+            render_svg(&instance, &[("OPT", &opt), ("ALG", &too_short)], None)
+        );
+    }
+}
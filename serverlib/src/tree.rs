@@ -0,0 +1,371 @@
+use crate::error::Error;
+use crate::metric::{Metric, Point};
+use std::fmt;
+use std::sync::Arc;
+
+/// Tolerance for rounding errors when servers move continuously along edges.
+pub const EPS: f32 = 1e-4;
+
+/// Represents a point of a tree, which is either a node or lies in the interior of an edge.
+///
+/// Every edge is identified with its lower node, hence a point is given by a `node` and the distance
+/// `up` to this node towards its parent. Nodes have `up == 0`. Points are kept canonical by `Tree::point`,
+/// such that equal points have equal representations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TreePoint {
+    node: usize,
+    up: f32,
+}
+
+impl TreePoint {
+    pub fn node(&self) -> usize {
+        self.node
+    }
+
+    pub fn up(&self) -> f32 {
+        self.up
+    }
+
+    pub fn is_node(&self) -> bool {
+        self.up == 0.0
+    }
+}
+
+impl From<usize> for TreePoint {
+    fn from(node: usize) -> TreePoint {
+        TreePoint { node, up: 0.0 }
+    }
+}
+
+impl fmt::Display for TreePoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_node() {
+            write!(f, "v{}", self.node)
+        } else {
+            write!(f, "v{}+{}", self.node, self.up)
+        }
+    }
+}
+
+/// Servers on a tree have no natural order, so they keep their indices.
+impl Point for TreePoint {}
+
+#[derive(Debug)]
+struct TreeData {
+    parent: Vec<Option<usize>>,
+    weight: Vec<f32>,
+    depth: Vec<f32>,
+    level: Vec<usize>,
+}
+
+/// Represents an edge-weighted tree, rooted at node `0`.
+///
+/// The tree data is shared, so cloning a tree is cheap.
+///
+/// ## Examples
+///
+/// ```
+/// # use serverlib::metric::Metric;
+/// # use serverlib::tree::{Tree, TreePoint};
+/// // A star with center 0 and leaves 1, 2, 3
+/// let tree = Tree::from_edges(4, &[(0, 1, 1.0), (0, 2, 2.0), (3, 0, 3.0)]).unwrap();
+/// assert_eq!(5.0, tree.distance(&TreePoint::from(2), &TreePoint::from(3)));
+/// let p = tree.point(2, 0.5);
+/// assert_eq!(2.5, tree.distance(&p, &TreePoint::from(1)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tree(Arc<TreeData>);
+
+impl Tree {
+    /// Builds a tree with nodes `0..n` from undirected weighted edges `(u, v, weight)`.
+    pub fn from_edges(n: usize, edges: &[(usize, usize, f32)]) -> Result<Tree, Error> {
+        if n == 0 {
+            return Err(Error::InvalidTree(
+                "A tree needs at least one node".to_string(),
+            ));
+        }
+        if edges.len() != n - 1 {
+            return Err(Error::InvalidTree(format!(
+                "A tree with {} nodes must have {} edges, but has {}",
+                n,
+                n - 1,
+                edges.len()
+            )));
+        }
+        let mut adjacent: Vec<Vec<(usize, f32)>> = vec![vec![]; n];
+        for &(u, v, w) in edges {
+            if u >= n || v >= n {
+                return Err(Error::InvalidTree(format!(
+                    "Edge ({}, {}) has unknown nodes",
+                    u, v
+                )));
+            }
+            if w <= 0.0 {
+                return Err(Error::InvalidTree(format!(
+                    "Edge ({}, {}) has weight {}",
+                    u, v, w
+                )));
+            }
+            adjacent[u].push((v, w));
+            adjacent[v].push((u, w));
+        }
+
+        let mut data = TreeData {
+            parent: vec![None; n],
+            weight: vec![0.0; n],
+            depth: vec![0.0; n],
+            level: vec![0; n],
+        };
+        let mut visited = vec![false; n];
+        visited[0] = true;
+        let mut stack = vec![0];
+        while let Some(u) = stack.pop() {
+            for &(v, w) in adjacent[u].iter() {
+                if !visited[v] {
+                    visited[v] = true;
+                    data.parent[v] = Some(u);
+                    data.weight[v] = w;
+                    data.depth[v] = data.depth[u] + w;
+                    data.level[v] = data.level[u] + 1;
+                    stack.push(v);
+                }
+            }
+        }
+        if visited.iter().any(|v| !v) {
+            return Err(Error::InvalidTree("The graph is not connected".to_string()));
+        }
+        Ok(Tree(Arc::new(data)))
+    }
+
+    pub fn number_of_nodes(&self) -> usize {
+        self.0.parent.len()
+    }
+
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        self.0.parent[node]
+    }
+
+    /// The weight of the edge between `node` and its parent.
+    pub fn weight(&self, node: usize) -> f32 {
+        self.0.weight[node]
+    }
+
+    /// The canonical point on the edge of `node` with distance `up` to `node`.
+    pub fn point(&self, node: usize, up: f32) -> TreePoint {
+        match self.parent(node) {
+            Some(parent) if up >= self.weight(node) - EPS => TreePoint::from(parent),
+            Some(_) if up > EPS => TreePoint { node, up },
+            _ => TreePoint::from(node),
+        }
+    }
+
+    fn lca(&self, mut u: usize, mut v: usize) -> usize {
+        while self.0.level[u] > self.0.level[v] {
+            u = self.0.parent[u].unwrap();
+        }
+        while self.0.level[v] > self.0.level[u] {
+            v = self.0.parent[v].unwrap();
+        }
+        while u != v {
+            u = self.0.parent[u].unwrap();
+            v = self.0.parent[v].unwrap();
+        }
+        u
+    }
+
+    pub fn node_distance(&self, u: usize, v: usize) -> f32 {
+        self.0.depth[u] + self.0.depth[v] - 2.0 * self.0.depth[self.lca(u, v)]
+    }
+
+    /// The nodes on the path from `u` to `v`.
+    fn node_path(&self, u: usize, v: usize) -> Vec<usize> {
+        let lca = self.lca(u, v);
+        let mut path = vec![u];
+        let mut x = u;
+        while x != lca {
+            x = self.0.parent[x].unwrap();
+            path.push(x);
+        }
+        let mut down = vec![];
+        let mut y = v;
+        while y != lca {
+            down.push(y);
+            y = self.0.parent[y].unwrap();
+        }
+        path.extend(down.into_iter().rev());
+        path
+    }
+
+    /// The end points of the edge containing `p` together with their distances to `p`.
+    fn end_points(&self, p: &TreePoint) -> Vec<(usize, f32)> {
+        match self.parent(p.node) {
+            Some(parent) if !p.is_node() => {
+                vec![(p.node, p.up), (parent, self.weight(p.node) - p.up)]
+            }
+            _ => vec![(p.node, 0.0)],
+        }
+    }
+
+    fn on_same_edge(&self, p: &TreePoint, q: &TreePoint) -> bool {
+        !p.is_node() && !q.is_node() && p.node == q.node
+    }
+
+    /// The points where the path from `p` to `q` changes its edge, including `p` and `q`.
+    pub fn path(&self, p: &TreePoint, q: &TreePoint) -> Vec<TreePoint> {
+        if self.on_same_edge(p, q) || p == q {
+            return vec![*p, *q];
+        }
+        let mut best: Option<(f32, usize, usize)> = None;
+        for &(u, du) in self.end_points(p).iter() {
+            for &(v, dv) in self.end_points(q).iter() {
+                let d = du + self.node_distance(u, v) + dv;
+                if best.is_none_or(|(b, _, _)| d < b) {
+                    best = Some((d, u, v));
+                }
+            }
+        }
+        let (_, u, v) = best.unwrap();
+        let mut path = vec![*p];
+        for node in self.node_path(u, v) {
+            let point = TreePoint::from(node);
+            if path.last() != Some(&point) {
+                path.push(point);
+            }
+        }
+        if path.last() != Some(q) {
+            path.push(*q);
+        }
+        path
+    }
+
+    /// Returns `true` if `x` lies on the path between `p` and `q`.
+    pub fn on_path(&self, x: &TreePoint, p: &TreePoint, q: &TreePoint) -> bool {
+        self.distance(p, x) + self.distance(x, q) <= self.distance(p, q) + EPS
+    }
+
+    /// Moves `p` by `delta` towards `q`, but not beyond `q`.
+    pub fn move_towards(&self, p: &TreePoint, q: &TreePoint, mut delta: f32) -> TreePoint {
+        let path = self.path(p, q);
+        for (a, b) in path.iter().zip(path.iter().skip(1)) {
+            let length = self.distance(a, b);
+            if delta < length - EPS {
+                return self.move_on_edge(a, b, delta);
+            }
+            delta -= length;
+        }
+        *q
+    }
+
+    /// Moves `a` by `delta` towards `b`, where both lie on the same edge.
+    fn move_on_edge(&self, a: &TreePoint, b: &TreePoint, delta: f32) -> TreePoint {
+        let child = if !a.is_node() {
+            a.node
+        } else if !b.is_node() || self.parent(b.node) == Some(a.node) {
+            b.node
+        } else {
+            a.node
+        };
+        let coordinate = |x: &TreePoint| {
+            if x.node == child {
+                x.up
+            } else {
+                self.weight(child)
+            }
+        };
+        let (from, to) = (coordinate(a), coordinate(b));
+        if to > from {
+            self.point(child, from + delta)
+        } else {
+            self.point(child, from - delta)
+        }
+    }
+}
+
+impl Metric for Tree {
+    type Point = TreePoint;
+
+    fn distance(&self, p: &TreePoint, q: &TreePoint) -> f32 {
+        if self.on_same_edge(p, q) {
+            return (p.up - q.up).abs();
+        }
+        let mut best = f32::INFINITY;
+        for &(u, du) in self.end_points(p).iter() {
+            for &(v, dv) in self.end_points(q).iter() {
+                best = best.min(du + self.node_distance(u, v) + dv);
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0 - 1 - 2 with a leaf 3 at node 1
+    fn get_tree() -> Tree {
+        Tree::from_edges(4, &[(0, 1, 2.0), (1, 2, 4.0), (3, 1, 1.0)]).unwrap()
+    }
+
+    #[test]
+    fn tree_rejects_invalid_edges() {
+        assert!(Tree::from_edges(3, &[(0, 1, 1.0)]).is_err());
+        assert!(Tree::from_edges(3, &[(0, 1, 1.0), (0, 1, 1.0)]).is_err());
+        assert!(Tree::from_edges(2, &[(0, 1, 0.0)]).is_err());
+    }
+
+    #[test]
+    fn tree_distance_works() {
+        let tree = get_tree();
+        assert_eq!(5.0, tree.distance(&TreePoint::from(2), &TreePoint::from(3)));
+        assert_eq!(6.0, tree.distance(&TreePoint::from(0), &TreePoint::from(2)));
+        let p = tree.point(2, 1.0);
+        let q = tree.point(2, 3.5);
+        assert_eq!(2.5, tree.distance(&p, &q));
+        assert_eq!(0.5, tree.distance(&q, &TreePoint::from(1)));
+        assert_eq!(4.0, tree.distance(&p, &TreePoint::from(3)));
+    }
+
+    #[test]
+    fn tree_point_is_canonical() {
+        let tree = get_tree();
+        assert_eq!(TreePoint::from(1), tree.point(2, 4.0));
+        assert_eq!(TreePoint::from(2), tree.point(2, 0.0));
+        assert_eq!(TreePoint::from(0), tree.point(0, 3.0));
+    }
+
+    #[test]
+    fn tree_path_works() {
+        let tree = get_tree();
+        let p = tree.point(2, 1.0);
+        assert_eq!(
+            vec![p, TreePoint::from(1), TreePoint::from(3)],
+            tree.path(&p, &TreePoint::from(3))
+        );
+        assert_eq!(
+            vec![p, TreePoint::from(2)],
+            tree.path(&p, &TreePoint::from(2))
+        );
+    }
+
+    #[test]
+    fn tree_move_towards_works() {
+        let tree = get_tree();
+        let start = TreePoint::from(2);
+        assert_eq!(
+            tree.point(2, 3.0),
+            tree.move_towards(&start, &TreePoint::from(3), 3.0)
+        );
+        assert_eq!(
+            tree.point(3, 0.5),
+            tree.move_towards(&start, &TreePoint::from(3), 4.5)
+        );
+        assert_eq!(
+            TreePoint::from(3),
+            tree.move_towards(&start, &TreePoint::from(3), 10.0)
+        );
+        assert!(tree.on_path(&TreePoint::from(1), &start, &TreePoint::from(0)));
+        assert!(!tree.on_path(&TreePoint::from(3), &start, &TreePoint::from(0)));
+    }
+}
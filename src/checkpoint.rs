@@ -0,0 +1,215 @@
+use crate::export::ExportConfig;
+use crate::opt_cache::fnv1a;
+
+use rand::Rng;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::{fmt, path};
+
+#[derive(Debug, Clone)]
+pub struct CheckpointError {
+    msg: String,
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid checkpoint: {}", self.msg)
+    }
+}
+
+impl Error for CheckpointError {
+    fn description(&self) -> &str {
+        &self.msg
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        None
+    }
+}
+
+impl CheckpointError {
+    fn new(msg: String) -> CheckpointError {
+        CheckpointError { msg }
+    }
+}
+
+/// Records the completed instances of a run, such that an interrupted run can be resumed.
+///
+/// An instance is completed once its results have been exported, see `pipeline::run`.
+///
+/// The checkpoint of the output `result.csv` is stored as `result.csv.<hash>.checkpoint` in the checkpoint
+/// directory, where the hash of the full output path separates runs with equally named outputs. Its first line is the seed of the run, which is drawn if none is given, and every further
+/// line contains the size of the output after an instance has been exported, followed by its name.
+#[derive(Debug)]
+pub struct Checkpoint {
+    file: File,
+    seed: u64,
+    completed: HashSet<String>,
+    offset: u64,
+}
+
+/// Opens the checkpoint of the output if a checkpoint directory is given.
+///
+/// Without `--resume`, a new checkpoint is started, which fails if there already is one.
+pub fn open(
+    config: &ExportConfig,
+    seed: Option<u64>,
+) -> Result<Option<Checkpoint>, Box<dyn Error>> {
+    let dir = match &config.checkpoint {
+        Some(dir) => path::Path::new(dir),
+        None => return Ok(None),
+    };
+    let output = path::Path::new(&config.output_file);
+    let name = output
+        .file_name()
+        .ok_or_else(|| CheckpointError::new(format!("{} is no file", config.output_file)))?;
+    let parent = match output.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => path::Path::new("."),
+    };
+    let output = fs::canonicalize(parent)
+        .map_err(|e| CheckpointError::new(format!("{}: {}", parent.display(), e)))?
+        .join(name);
+    let path = dir.join(format!(
+        "{}.{:016x}.checkpoint",
+        name.to_string_lossy(),
+        fnv1a(output.to_string_lossy().as_bytes()) as u64
+    ));
+    if config.resume {
+        Checkpoint::resume(&path, seed).map(Some)
+    } else if path.exists() {
+        Err(CheckpointError::new(format!(
+            "{} already exists, use --resume to continue the run",
+            path.display()
+        ))
+        .into())
+    } else {
+        fs::create_dir_all(dir)?;
+        Checkpoint::create(&path, seed.unwrap_or_else(|| rand::thread_rng().gen())).map(Some)
+    }
+}
+
+impl Checkpoint {
+    fn create(path: &path::Path, seed: u64) -> Result<Checkpoint, Box<dyn Error>> {
+        let mut file = File::create(path)?;
+        writeln!(file, "{}", seed)?;
+        file.sync_data()?;
+        Ok(Checkpoint {
+            file,
+            seed,
+            completed: HashSet::new(),
+            offset: 0,
+        })
+    }
+
+    fn resume(path: &path::Path, seed: Option<u64>) -> Result<Checkpoint, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        // an interrupted write leaves an incomplete last line, which is discarded
+        let lines: Vec<&str> = content
+            .split_inclusive('\n')
+            .take_while(|line| line.ends_with('\n'))
+            .collect();
+        let file = OpenOptions::new().append(true).open(path)?;
+        file.set_len(lines.iter().map(|line| line.len() as u64).sum())?;
+
+        let mut lines = lines.into_iter().map(str::trim_end);
+        let recorded: u64 = lines
+            .next()
+            .and_then(|line| line.parse().ok())
+            .ok_or_else(|| CheckpointError::new(format!("{} has no seed", path.display())))?;
+        if seed.is_some_and(|seed| seed != recorded) {
+            return Err(CheckpointError::new(format!(
+                "the run has been started with seed {}",
+                recorded
+            ))
+            .into());
+        }
+        let mut completed = HashSet::new();
+        let mut offset = 0;
+        for line in lines {
+            let invalid = || {
+                CheckpointError::new(format!(
+                    "{} contains the invalid line {}",
+                    path.display(),
+                    line
+                ))
+            };
+            let (size, name) = line.split_once('\t').ok_or_else(invalid)?;
+            offset = size.parse().map_err(|_| invalid())?;
+            completed.insert(name.to_string());
+        }
+        Ok(Checkpoint {
+            file,
+            seed: recorded,
+            completed,
+            offset,
+        })
+    }
+
+    /// The seed of the run.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The size of the output up to the last completed instance.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn is_completed(&self, name: &str) -> bool {
+        self.completed.contains(name)
+    }
+
+    /// Records that the instance has been exported and the output has now `offset` bytes.
+    pub fn complete(&mut self, name: &str, offset: u64) -> Result<(), Box<dyn Error>> {
+        writeln!(self.file, "{}\t{}", offset, name)?;
+        self.file.sync_data()?;
+        self.completed.insert(name.to_string());
+        self.offset = offset;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoint_resume_works() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join("serversim_checkpoint_test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("a"))?;
+        fs::create_dir_all(dir.join("b"))?;
+        let config_of = |output: &str| ExportConfig {
+            output_file: dir.join(output).to_string_lossy().to_string(),
+            checkpoint: Some(dir.join("checkpoints").to_string_lossy().to_string()),
+            resume: false,
+        };
+        let mut config = config_of("a/result.csv");
+        let mut checkpoint = open(&config, None)?.unwrap();
+        let seed = checkpoint.seed();
+        checkpoint.complete("a.txt", 100)?;
+        checkpoint.complete("b.txt", 150)?;
+        // a second run must not overwrite the checkpoint
+        assert!(open(&config, None).is_err());
+
+        config.resume = true;
+        let checkpoint = open(&config, None)?.unwrap();
+        assert_eq!(seed, checkpoint.seed());
+        assert_eq!(150, checkpoint.offset());
+        assert!(checkpoint.is_completed("a.txt"));
+        assert!(!checkpoint.is_completed("c.txt"));
+        assert!(open(&config, Some(seed.wrapping_add(1))).is_err());
+
+        // an equally named output in another directory has its own checkpoint
+        let mut other = config_of("b/result.csv");
+        assert!(open(&other, None)?.is_some());
+        other.resume = true;
+        assert!(!open(&other, None)?.unwrap().is_completed("a.txt"));
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
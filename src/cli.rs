@@ -1,16 +1,30 @@
+use crate::checkpoint;
+use crate::experiment;
 use crate::export;
 use crate::instance_generator;
+use crate::pipeline;
+use crate::plot;
 use crate::sample_generator;
 use crate::sim;
+use crate::trace;
+use crate::visualize;
+use console::style;
+use samplelib::Sample;
 use std::error::Error;
+use std::path;
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "server-simulation")]
 struct Cli {
+    /// Validate the schedules of the algorithms and report invalid samples
     #[structopt(short, long)]
     debug: bool,
 
+    /// Seed of all random choices, which makes runs reproducible
+    #[structopt(long)]
+    seed: Option<u64>,
+
     #[structopt(flatten)]
     instance_config: instance_generator::InstanceConfig,
 
@@ -44,28 +58,364 @@ pub enum Generators {
         #[structopt(subcommand)]
         simulator: sim::Simulators,
     },
+
+    /// Trace the k-server algorithms request by request on a single instance
+    #[structopt(name = "trace")]
+    Trace {
+        #[structopt(flatten)]
+        config: trace::TraceConfig,
+    },
+
+    /// Draw OPT and k-server algorithms on a single instance as an SVG space-time diagram
+    #[structopt(name = "visualize")]
+    Visualize {
+        #[structopt(flatten)]
+        config: visualize::VisualizeConfig,
+    },
+
+    /// Plot the competitive ratios of an exported CSV, binned by eta with the bin size -b
+    #[structopt(name = "plot")]
+    Plot {
+        #[structopt(flatten)]
+        config: plot::PlotConfig,
+    },
+
+    /// Run all simulations described by an experiment file, see `experiment::Experiment`
+    #[structopt(name = "run")]
+    Run { file: String },
+
+    /// Solve instances on the line, generate their predictions and save the samples to a file (JSON if it
+    /// ends with `.json`)
+    #[structopt(name = "generate-samples")]
+    GenerateSamples {
+        file: String,
+
+        #[structopt(subcommand)]
+        instances: Instances,
+    },
+
+    /// Simulate the samples saved by `generate-samples` with their predictions
+    #[structopt(name = "simulate")]
+    Simulate {
+        #[structopt(long)]
+        samples: String,
+
+        #[structopt(subcommand)]
+        simulator: sim::Simulators,
+    },
+}
+
+/// The instances of `generate-samples`.
+#[derive(StructOpt, Debug)]
+pub enum Instances {
+    #[structopt(name = "sample")]
+    Sample {
+        #[structopt(flatten)]
+        config: instance_generator::InstanceSampleConfig,
+    },
+
+    #[structopt(name = "load_instances")]
+    LoadInstances {
+        #[structopt(flatten)]
+        config: instance_generator::InstanceLoadConfig,
+    },
 }
 
 pub fn run() -> Result<(), Box<dyn Error>> {
-    let cli = Cli::from_args();
+    execute(Cli::from_args())
+}
 
+fn execute(cli: Cli) -> Result<(), Box<dyn Error>> {
     println!("{:?}", cli);
+    let checkpoint = match cli.generator {
+        Generators::Sample { .. }
+        | Generators::LoadInstances { .. }
+        | Generators::Simulate { .. } => checkpoint::open(&cli.export_config, cli.seed)?,
+        _ => None,
+    };
+    // a resumed run continues with the seed of the checkpoint
+    let seed = checkpoint.as_ref().map_or(cli.seed, |c| Some(c.seed()));
     let simu: sim::Simulators;
     let instances = match cli.generator {
         Generators::Sample { config, simulator } => {
             simu = simulator;
-            instance_generator::generate_instances(&config, &cli.instance_config)?
+            instance_generator::generate_instances(&config, &cli.instance_config, seed)?
+        }
+        Generators::LoadInstances { config, simulator } if config.circle.is_some() => {
+            let instances =
+                instance_generator::load_circle_instances(&config, &cli.instance_config)?;
+            check_kserver_only(&simulator, "the circle")?;
+            return pipeline::run(
+                instances,
+                &cli.sample_config,
+                &simulator,
+                &cli.export_config,
+                cli.debug,
+                seed,
+                checkpoint,
+            );
+        }
+        Generators::LoadInstances { config, simulator } if config.plane => {
+            let instances =
+                instance_generator::load_plane_instances(&config, &cli.instance_config)?;
+            check_kserver_only(&simulator, "the plane")?;
+            return pipeline::run(
+                instances,
+                &cli.sample_config,
+                &simulator,
+                &cli.export_config,
+                cli.debug,
+                seed,
+                checkpoint,
+            );
+        }
+        Generators::LoadInstances { config, simulator } if config.tree => {
+            let instances = instance_generator::load_tree_instances(&config, &cli.instance_config)?;
+            check_kserver_only(&simulator, "trees")?;
+            return pipeline::run(
+                instances,
+                &cli.sample_config,
+                &simulator,
+                &cli.export_config,
+                cli.debug,
+                seed,
+                checkpoint,
+            );
         }
         Generators::LoadInstances { config, simulator } => {
             simu = simulator;
             instance_generator::load_instances(&config, &cli.instance_config)?
         }
+        Generators::Trace { config } => {
+            let sample = load_sample(
+                &config.file,
+                &cli.instance_config,
+                &cli.sample_config,
+                cli.seed,
+            )?;
+            return trace::run(&sample, &config, &cli.export_config.output_file);
+        }
+        Generators::Visualize { config } => {
+            let sample = load_sample(
+                &config.file,
+                &cli.instance_config,
+                &cli.sample_config,
+                cli.seed,
+            )?;
+            return visualize::run(&sample, &config);
+        }
+        Generators::Plot { config } => {
+            return plot::run(&config, cli.sample_config.pred_config.step_size as f64);
+        }
+        Generators::Run { file } => return run_experiment(&file),
+        Generators::GenerateSamples { file, instances } => {
+            let instances = match instances {
+                Instances::Sample { config } => {
+                    instance_generator::generate_instances(&config, &cli.instance_config, seed)?
+                }
+                Instances::LoadInstances { config }
+                    if config.circle.is_some() || config.plane || config.tree =>
+                {
+                    return Err(sim::SimulatorError::new(
+                        "Only samples on the line can be saved".to_string(),
+                    )
+                    .into());
+                }
+                Instances::LoadInstances { config } => {
+                    instance_generator::load_instances(&config, &cli.instance_config)?
+                }
+            };
+            let samples = sample_generator::run(instances, &cli.sample_config, seed)?;
+            return sample_generator::save(&samples, &file);
+        }
+        Generators::Simulate { samples, simulator } => {
+            let samples = sample_generator::load(&samples)?;
+            for (_, _, sample) in &samples {
+                sim::check_instances(&simulator, sample.instance.k(), sample.instance.length())?;
+            }
+            return pipeline::run_samples(
+                samples,
+                &simulator,
+                &cli.export_config,
+                cli.debug,
+                seed,
+                checkpoint,
+            );
+        }
     };
+    sim::check_instances(
+        &simu,
+        cli.instance_config.number_of_servers,
+        cli.instance_config.number_of_requests,
+    )?;
 
-    let samples = sample_generator::run(instances, &cli.sample_config)?;
+    pipeline::run(
+        instances,
+        &cli.sample_config,
+        &simu,
+        &cli.export_config,
+        cli.debug,
+        seed,
+        checkpoint,
+    )
+}
+
+/// Runs the simulations of an experiment file one after another.
+fn run_experiment(file: &str) -> Result<(), Box<dyn Error>> {
+    let runs = experiment::load(file)?.to_args()?;
+    for (i, args) in runs.iter().enumerate() {
+        println!(
+            "{} {}",
+            style(format!("Experiment run {}/{}:", i + 1, runs.len()))
+                .bold()
+                .cyan(),
+            args[1..].join(" ")
+        );
+        execute(Cli::from_iter_safe(args)?)?;
+    }
+    Ok(())
+}
 
-    let results = sim::run(samples, simu);
-    export::run(results, &cli.export_config)?;
+/// Loads a single instance on the line and builds its sample.
+fn load_sample(
+    file: &str,
+    instance_config: &instance_generator::InstanceConfig,
+    sample_config: &sample_generator::SampleConfig,
+    seed: Option<u64>,
+) -> Result<Sample, Box<dyn Error>> {
+    let instance = instance_generator::load_instance(path::Path::new(file), instance_config)?;
+    sample_generator::run(vec![(file.to_string(), instance)], sample_config, seed)?
+        .pop()
+        .map(|(_, _, sample)| sample)
+        .ok_or_else(|| {
+            sim::SimulatorError::new(format!(
+                "{} cannot be solved or has no valid predictions",
+                file
+            ))
+            .into()
+        })
+}
 
+/// Metric spaces other than the line only support k-server.
+fn check_kserver_only(simulator: &sim::Simulators, name: &str) -> Result<(), sim::SimulatorError> {
+    if let sim::Simulators::KTaxi(_) = simulator {
+        return Err(sim::SimulatorError::new(format!(
+            "k-taxi is not supported on {}",
+            name
+        )));
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn experiment_args_are_valid() {
+        let experiment: experiment::Experiment = toml::from_str(
+            r#"
+            output = "taxi_k{k}_g{gamma}.csv"
+            debug = true
+
+            [instances]
+            source = "sample"
+            number = 10
+            relocations = 0.5
+            servers = [2, 3]
+            length = 100
+            min = -10
+            max = 10
+
+            [predictions]
+            number = 4
+            bin_size = 0.5
+            samples_per_round = 10
+            per_bin = 2
+            solver = "dp"
+            cost_model = "hard"
+            opt_cache = "cache"
+
+            [simulation]
+            problem = "ktaxi"
+            number_of_lambdas = 3
+            gammas = [0.5, 1.0]
+            lazy = true
+            wfa = true
+            general = true
+            runs = 2
+            beta = 0.1
+            combine = ["dc", "ldc"]
+            "#,
+        )
+        .unwrap();
+        let runs = experiment.to_args().unwrap();
+        assert_eq!(4, runs.len());
+        let cli = Cli::from_iter_safe(&runs[1]).unwrap();
+        assert!(cli.debug);
+        assert_eq!(2, cli.instance_config.number_of_servers);
+        assert_eq!(-10, cli.instance_config.min_value);
+        assert_eq!("taxi_k2_g1.csv", cli.export_config.output_file);
+        assert_eq!(Some("cache"), cli.sample_config.opt_cache.as_deref());
+        match cli.generator {
+            Generators::Sample {
+                config,
+                simulator: sim::Simulators::KTaxi(sim),
+            } => {
+                assert_eq!(10, config.number_of_instances);
+                assert_eq!(1.0, sim.gamma);
+                assert!(sim.general);
+                assert_eq!(vec!["dc", "ldc"], sim.combine);
+            }
+            _ => panic!("Expected to sample k-taxi instances"),
+        }
+
+        let mut experiment = experiment;
+        experiment.instances.source = experiment::InstanceSource::Load;
+        experiment.instances.number = Some(-1);
+        experiment.instances.plane = true;
+        for args in experiment.to_args().unwrap() {
+            assert!(Cli::from_iter_safe(&args).is_ok());
+        }
+        experiment.instances.plane = false;
+        experiment.instances.tree = true;
+        for args in experiment.to_args().unwrap() {
+            let cli = Cli::from_iter_safe(&args).unwrap();
+            assert!(
+                matches!(cli.generator, Generators::LoadInstances { config, .. } if config.tree)
+            );
+        }
+    }
+
+    #[test]
+    fn beta_must_be_a_learning_rate() {
+        let parse =
+            |beta| Cli::from_iter_safe(&["serversim", "sample", "1", "kserver", "--beta", beta]);
+        assert!(parse("0.3").is_ok());
+        for beta in &["0", "1", "1.5", "-0.5", "x"] {
+            assert!(parse(beta).is_err());
+        }
+    }
+
+    #[test]
+    fn gamma_must_be_positive() {
+        let parse =
+            |gamma| Cli::from_iter_safe(&["serversim", "sample", "1", "kserver", "--gamma", gamma]);
+        assert!(parse("0.5").is_ok());
+        assert!(parse("2").is_ok());
+        for gamma in &["0", "-1", "inf", "NaN", "x"] {
+            assert!(parse(gamma).is_err());
+        }
+    }
+
+    #[test]
+    fn wfa_is_rejected_for_large_instances() {
+        let simulator = sim::Simulators::from_iter(&["test", "kserver", "--wfa"]);
+        assert!(sim::check_instances(&simulator, 2, 1000).is_ok());
+        assert!(sim::check_instances(&simulator, 10, 1000).is_err());
+        let simulator = sim::Simulators::from_iter(&["test", "kserver", "--combine", "dc,wfa"]);
+        assert!(sim::check_instances(&simulator, 10, 1000).is_err());
+        let simulator = sim::Simulators::from_iter(&["test", "kserver"]);
+        assert!(sim::check_instances(&simulator, 10, 1000).is_ok());
+    }
+}
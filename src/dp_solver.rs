@@ -0,0 +1,168 @@
+use serverlib::prelude::*;
+
+use crate::solver::SolverError;
+use std::collections::BTreeMap;
+
+/// Computes an optimal solution with a dynamic program over server configurations.
+///
+/// It is sufficient to consider lazy schedules, i.e., after serving the `t`-th request, every
+/// server is located at an initial position or the end point of a previous request. The table of
+/// step `t` is the work function restricted to configurations containing the `t`-th request. States
+/// are keyed by their sorted positions, so repeated request positions share a state. The number of
+/// states grows like `n^(k-1)`, hence this is only tractable for small `k`.
+pub trait DPSolver {
+    fn solve_dp(&self) -> Result<(Schedule, f64), SolverError>;
+}
+
+type State = Vec<u32>;
+
+impl DPSolver for Instance {
+    fn solve_dp(&self) -> Result<(Schedule, f64), SolverError> {
+        if self.k() == 0 {
+            return Err(SolverError::new("Instance has no servers".to_string()));
+        }
+        let initial = key(&self
+            .initial_positions()
+            .into_iter()
+            .copied()
+            .collect::<Vec<f32>>());
+
+        // For every step, maps a state to its optimal cost and its predecessor state.
+        let mut tables: Vec<BTreeMap<State, (f64, State)>> = Vec::with_capacity(self.length() + 1);
+        let mut first = BTreeMap::new();
+        first.insert(initial.clone(), (0.0, initial));
+        tables.push(first);
+
+        for req in self.requests() {
+            let mut next: BTreeMap<State, (f64, State)> = BTreeMap::new();
+            for (state, &(cost, _)) in tables.last().unwrap().iter() {
+                let positions = state
+                    .iter()
+                    .map(|&b| f32::from_bits(b))
+                    .collect::<Vec<f32>>();
+                for (i, pos) in positions.iter().enumerate() {
+                    if i > 0 && positions[i - 1] == *pos {
+                        // Moving one of several servers at the same position yields the same state.
+                        continue;
+                    }
+                    let mut moved = positions.clone();
+                    moved[i] = *req.end();
+                    moved.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                    let moved_cost = cost + req.distance_from(pos) as f64;
+                    let entry = next
+                        .entry(key(&moved))
+                        .or_insert((f64::INFINITY, state.clone()));
+                    if moved_cost < entry.0 {
+                        *entry = (moved_cost, state.clone());
+                    }
+                }
+            }
+            tables.push(next);
+        }
+
+        let (last, &(opt_cost, _)) = tables
+            .last()
+            .unwrap()
+            .iter()
+            .min_by(|(_, (c1, _)), (_, (c2, _))| c1.partial_cmp(c2).unwrap())
+            .ok_or_else(|| SolverError::new("No feasible solution found".to_string()))?;
+
+        let mut configs = vec![last.clone()];
+        for table in tables.iter().skip(1).rev() {
+            let (_, pred) = &table[configs.last().unwrap()];
+            configs.push(pred.clone());
+        }
+
+        let schedule = configs
+            .into_iter()
+            .rev()
+            .map(|state| {
+                ServerConfiguration::from(
+                    state.into_iter().map(f32::from_bits).collect::<Vec<f32>>(),
+                )
+            })
+            .collect::<Schedule>();
+        Ok((schedule, opt_cost))
+    }
+}
+
+fn key(positions: &[f32]) -> State {
+    // Adding 0.0 maps -0.0 to 0.0, so that both have the same key.
+    positions.iter().map(|&x| (x + 0.0).to_bits()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Solver;
+    use rand::distributions::{Distribution, Uniform};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::error::Error;
+
+    #[test]
+    fn dp_solver_costs_works() -> Result<(), Box<dyn Error>> {
+        let instance = Instance::from((vec![78, 77, 30, 8, 15, 58, 37, 19, 11, 7], vec![91, 91]));
+        let (schedule, costs) = instance.solve_dp()?;
+        assert_eq!(160.0, costs);
+        assert_eq!(160.0, schedule.cost());
+        Ok(())
+    }
+
+    #[test]
+    fn dp_solver_works() -> Result<(), Box<dyn Error>> {
+        let instance = Instance::from((vec![17, 17, 5, 14, 16, 17], vec![14, 14]));
+        let solution = Schedule::from(vec![
+            vec![14, 14],
+            vec![14, 17],
+            vec![14, 17],
+            vec![5, 14],
+            vec![5, 14],
+            vec![5, 16],
+            vec![5, 17],
+        ]);
+        assert_eq!(solution, instance.solve_dp()?.0);
+        Ok(())
+    }
+
+    #[test]
+    fn dp_solver_fractional_positions() -> Result<(), Box<dyn Error>> {
+        let instance = Instance::from((vec![0.5, 2.25, 0.75], vec![1.0, 1.0]));
+        let (_, costs) = instance.solve_dp()?;
+        assert_eq!(2.0, costs);
+        Ok(())
+    }
+
+    #[test]
+    fn dp_solver_matches_min_cost_flow() -> Result<(), Box<dyn Error>> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let dist = Uniform::from(0..200);
+        for k in 1..=3 {
+            for _ in 0..20 {
+                let requests = (0..15).map(|_| dist.sample(&mut rng)).collect::<Vec<i32>>();
+                let instance = Instance::from((requests, vec![dist.sample(&mut rng); k]));
+                let (schedule, dp_costs) = instance.solve_dp()?;
+                let (_, flow_costs) = instance.solve()?;
+                assert_eq!(flow_costs, dp_costs, "{}", instance);
+                assert_eq!(dp_costs, schedule.cost(), "{}", instance);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn dp_solver_matches_min_cost_flow_on_taxi_instances() -> Result<(), Box<dyn Error>> {
+        let mut rng = StdRng::seed_from_u64(1);
+        let dist = Uniform::from(0..200);
+        for _ in 0..20 {
+            let requests = (0..15)
+                .map(|_| (dist.sample(&mut rng), dist.sample(&mut rng)))
+                .collect::<Vec<(i32, i32)>>();
+            let instance = Instance::from((requests, vec![dist.sample(&mut rng); 2]));
+            let (_, dp_costs) = instance.solve_dp()?;
+            let (_, flow_costs) = instance.solve()?;
+            assert_eq!(flow_costs, dp_costs, "{}", instance);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,359 @@
+use serde::Deserialize;
+use std::error::Error;
+use std::{fmt, fs};
+
+/// An experiment file, which describes simulation runs declaratively.
+///
+/// Every run is translated into the arguments of the corresponding command line invocation, such that
+/// all defaults and checks are shared with the flags. Unset values fall back to the defaults of the flags.
+///
+/// ## Examples
+///
+/// ```toml
+/// output = "bk_k{k}.csv"
+///
+/// [instances]
+/// source = "load"
+/// directory = "data"
+/// prefix = "bk"
+/// servers = [2, 10, 50]
+/// length = 1000
+///
+/// [predictions]
+/// number = 10
+/// bin_size = 1.0
+/// samples_per_round = 100
+/// per_bin = 5
+///
+/// [simulation]
+/// lambdas = [0.0, 0.1, 0.5]
+/// gammas = [1.0]
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Experiment {
+    /// The CSV file of the results, which has to contain `{k}` (`{gamma}`) if several k (gammas) are given.
+    pub output: String,
+    #[serde(default)]
+    pub debug: bool,
+    /// The seed of all runs, which makes the experiment reproducible
+    pub seed: Option<u64>,
+    /// The checkpoint directory, which is shared by all runs since their outputs differ
+    pub checkpoint: Option<String>,
+    #[serde(default)]
+    pub resume: bool,
+    pub instances: InstanceSpec,
+    #[serde(default)]
+    pub predictions: PredictionSpec,
+    #[serde(default)]
+    pub simulation: SimulationSpec,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum InstanceSource {
+    Sample,
+    Load,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct InstanceSpec {
+    pub source: InstanceSource,
+    /// The number of sampled instances, or the maximal number of loaded instances
+    pub number: Option<i64>,
+    /// The percentage of relocations of sampled instances
+    pub relocations: Option<f32>,
+    pub directory: Option<String>,
+    pub prefix: Option<String>,
+    pub circle: Option<f32>,
+    #[serde(default)]
+    pub plane: bool,
+    /// Load instances on weighted trees, see `instance_generator::parse_tree_instance`
+    #[serde(default)]
+    pub tree: bool,
+    /// The numbers of servers, each of which is simulated in a separate run
+    #[serde(default)]
+    pub servers: Vec<usize>,
+    pub length: Option<usize>,
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PredictionSpec {
+    pub number: Option<usize>,
+    pub bin_size: Option<f32>,
+    pub samples_per_round: Option<usize>,
+    pub per_bin: Option<usize>,
+    pub solver: Option<String>,
+    pub cost_model: Option<String>,
+    /// The directory of the OPT cache, which is shared by all runs
+    pub opt_cache: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Problem {
+    #[default]
+    KServer,
+    KTaxi,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SimulationSpec {
+    #[serde(default)]
+    pub problem: Problem,
+    /// The simulated lambdas, or `number_of_lambdas` evenly spaced ones in [0,1] if empty
+    #[serde(default)]
+    pub lambdas: Vec<f32>,
+    pub number_of_lambdas: Option<usize>,
+    /// The gammas, each of which is simulated in a separate run
+    #[serde(default)]
+    pub gammas: Vec<f64>,
+    #[serde(default)]
+    pub lazy: bool,
+    #[serde(default)]
+    pub wfa: bool,
+    /// Simulate the k-taxi algorithms for any number of servers instead of BiasedDC
+    #[serde(default)]
+    pub general: bool,
+    pub runs: Option<usize>,
+    pub beta: Option<f64>,
+    /// The algorithms of the combination, e.g. `["ftp", "dc", "ldc"]`
+    #[serde(default)]
+    pub combine: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExperimentError {
+    msg: String,
+}
+
+impl fmt::Display for ExperimentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid experiment: {}", self.msg)
+    }
+}
+
+impl Error for ExperimentError {
+    fn description(&self) -> &str {
+        &self.msg
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        None
+    }
+}
+
+impl ExperimentError {
+    fn new(msg: String) -> ExperimentError {
+        ExperimentError { msg }
+    }
+}
+
+/// Reads an experiment file.
+pub fn load(path: &str) -> Result<Experiment, Box<dyn Error>> {
+    Ok(toml::from_str(&fs::read_to_string(path)?)?)
+}
+
+fn flag<T: ToString>(args: &mut Vec<String>, name: &str, value: &Option<T>) {
+    if let Some(value) = value {
+        // `--name=value` also works for negative values
+        args.push(format!("--{}={}", name, value.to_string()));
+    }
+}
+
+fn switch(args: &mut Vec<String>, name: &str, value: bool) {
+    if value {
+        args.push(format!("--{}", name));
+    }
+}
+
+fn list<T: ToString>(args: &mut Vec<String>, name: &str, values: &[T]) {
+    if !values.is_empty() {
+        let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        args.push(format!("--{}={}", name, values.join(",")));
+    }
+}
+
+/// Substitutes `placeholder` in the output file, which is required if there are several `values`.
+fn expand<T: ToString>(
+    output: &str,
+    placeholder: &str,
+    values: &[T],
+) -> Result<Vec<(String, Option<String>)>, ExperimentError> {
+    if values.len() > 1 && !output.contains(placeholder) {
+        return Err(ExperimentError::new(format!(
+            "the output has to contain {} to distinguish the runs",
+            placeholder
+        )));
+    }
+    if values.is_empty() {
+        return Ok(vec![(output.to_string(), None)]);
+    }
+    Ok(values
+        .iter()
+        .map(|v| {
+            (
+                output.replace(placeholder, &v.to_string()),
+                Some(v.to_string()),
+            )
+        })
+        .collect())
+}
+
+impl Experiment {
+    /// The command line arguments of every run of the experiment.
+    pub fn to_args(&self) -> Result<Vec<Vec<String>>, ExperimentError> {
+        let mut runs = vec![];
+        for (output, k) in expand(&self.output, "{k}", &self.instances.servers)? {
+            for (output, gamma) in expand(&output, "{gamma}", &self.simulation.gammas)? {
+                runs.push(self.run_args(output, k.clone(), gamma));
+            }
+        }
+        Ok(runs)
+    }
+
+    fn run_args(&self, output: String, k: Option<String>, gamma: Option<String>) -> Vec<String> {
+        let instances = &self.instances;
+        let predictions = &self.predictions;
+        let simulation = &self.simulation;
+
+        let mut args = vec!["serversim".to_string()];
+        switch(&mut args, "debug", self.debug);
+        flag(&mut args, "seed", &self.seed);
+        flag(&mut args, "servers", &k);
+        flag(&mut args, "length", &instances.length);
+        flag(&mut args, "min", &instances.min);
+        flag(&mut args, "max", &instances.max);
+        flag(&mut args, "preds", &predictions.number);
+        flag(&mut args, "preds_bin_size", &predictions.bin_size);
+        flag(
+            &mut args,
+            "preds_samples_per_round",
+            &predictions.samples_per_round,
+        );
+        flag(&mut args, "preds_per_bin", &predictions.per_bin);
+        flag(&mut args, "solver", &predictions.solver);
+        flag(&mut args, "cost_model", &predictions.cost_model);
+        flag(&mut args, "opt-cache", &predictions.opt_cache);
+        flag(&mut args, "output", &Some(output));
+        flag(&mut args, "checkpoint", &self.checkpoint);
+        switch(&mut args, "resume", self.resume);
+
+        match instances.source {
+            InstanceSource::Sample => {
+                args.push("sample".to_string());
+                args.push(instances.number.unwrap_or(1).to_string());
+                flag(&mut args, "relocations", &instances.relocations);
+            }
+            InstanceSource::Load => {
+                args.push("load_instances".to_string());
+                args.push(instances.prefix.clone().unwrap_or_default());
+                flag(&mut args, "number-of-instances", &instances.number);
+                flag(&mut args, "directory", &instances.directory);
+                flag(&mut args, "circle", &instances.circle);
+                switch(&mut args, "plane", instances.plane);
+                switch(&mut args, "tree", instances.tree);
+            }
+        }
+
+        args.push(
+            match simulation.problem {
+                Problem::KServer => "kserver",
+                Problem::KTaxi => "ktaxi",
+            }
+            .to_string(),
+        );
+        flag(&mut args, "lambdas", &simulation.number_of_lambdas);
+        list(&mut args, "lambda_values", &simulation.lambdas);
+        flag(&mut args, "gamma", &gamma);
+        switch(&mut args, "lazy", simulation.lazy);
+        switch(&mut args, "wfa", simulation.wfa);
+        switch(&mut args, "general", simulation.general);
+        flag(&mut args, "runs", &simulation.runs);
+        flag(&mut args, "beta", &simulation.beta);
+        list(&mut args, "combine", &simulation.combine);
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn experiment_to_args_works() {
+        let experiment: Experiment = toml::from_str(
+            r#"
+            output = "bk_k{k}_g{gamma}.csv"
+            seed = 42
+            checkpoint = "ckpt"
+
+            [instances]
+            source = "load"
+            directory = "data"
+            prefix = "bk"
+            servers = [2, 10]
+            min = -5
+
+            [predictions]
+            bin_size = 1.0
+
+            [simulation]
+            lambdas = [0.0, 0.5]
+            gammas = [1.0, 2.0]
+            combine = ["ftp", "dc"]
+            "#,
+        )
+        .unwrap();
+        let runs = experiment.to_args().unwrap();
+        assert_eq!(4, runs.len());
+        assert_eq!(
+            vec![
+                "serversim",
+                "--seed=42",
+                "--servers=10",
+                "--min=-5",
+                "--preds_bin_size=1",
+                "--output=bk_k10_g2.csv",
+                "--checkpoint=ckpt",
+                "load_instances",
+                "bk",
+                "--directory=data",
+                "kserver",
+                "--lambda_values=0,0.5",
+                "--gamma=2",
+                "--combine=ftp,dc",
+            ],
+            runs[3]
+        );
+    }
+
+    #[test]
+    fn experiment_requires_placeholders() {
+        let experiment: Experiment = toml::from_str(
+            r#"
+            output = "result.csv"
+            [instances]
+            source = "sample"
+            servers = [2, 3]
+            "#,
+        )
+        .unwrap();
+        assert!(experiment.to_args().is_err());
+
+        let unknown: Result<Experiment, _> = toml::from_str(
+            r#"
+            output = "result.csv"
+            [instances]
+            source = "sample"
+            k = 2
+            "#,
+        );
+        assert!(unknown.is_err());
+    }
+}
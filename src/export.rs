@@ -1,59 +1,110 @@
 use samplelib::*;
 
 use console::style;
-use csv::WriterBuilder;
-use indicatif::{ProgressBar, ProgressIterator, ProgressStyle};
+use csv::{Writer, WriterBuilder};
 use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{Seek, SeekFrom};
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
 pub struct ExportConfig {
     #[structopt(short = "o", long = "output", default_value = "result.csv")]
     pub output_file: String,
+
+    /// Record the completed instances in this directory, such that the run can be resumed
+    #[structopt(long)]
+    pub checkpoint: Option<String>,
+
+    /// Continue the run recorded in the checkpoint directory and skip its completed instances
+    #[structopt(long, requires = "checkpoint")]
+    pub resume: bool,
+}
+
+/// Writes results to the CSV file as soon as they are simulated, with an additional `Seed` column if the
+/// run is seeded. The header is derived from the algorithms of the first written results.
+pub struct Exporter {
+    wtr: Writer<File>,
+    seed: Option<u64>,
+    has_headers: bool,
+    number_of_results: usize,
 }
 
+impl Exporter {
+    /// Opens the output and truncates it to `offset` bytes, which keeps the results of a resumed run.
+    pub fn new(
+        config: &ExportConfig,
+        seed: Option<u64>,
+        offset: u64,
+    ) -> Result<Exporter, Box<dyn Error>> {
+        println!(
+            "{}",
+            style(format!("Exporting to {}...", config.output_file))
+                .bold()
+                .cyan()
+        );
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(offset == 0)
+            .open(&config.output_file)?;
+        file.set_len(offset)?;
+        file.seek(SeekFrom::End(0))?;
+        let wtr = WriterBuilder::new().has_headers(false).from_writer(file);
+        Ok(Exporter {
+            wtr,
+            seed,
+            has_headers: offset > 0,
+            number_of_results: 0,
+        })
+    }
 
+    /// The size of the output, including all written results.
+    pub fn size(&self) -> Result<u64, Box<dyn Error>> {
+        Ok(self.wtr.get_ref().metadata()?.len())
+    }
 
-pub fn run(results: Vec<SimResult>, config: &ExportConfig) -> Result<(), Box<dyn Error>> {
-    println!(
-        "{}",
-        style(format!("Start exporting to {}...", config.output_file))
-            .bold()
-            .cyan()
-    );
-    let mut wtr = WriterBuilder::new()
-            .has_headers(false)
-            .from_path(config.output_file.clone())?;
-    let pb = ProgressBar::new(results.len() as u64);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] ({percent}%)"),
-    );
-
-    let mut headers = vec!["NumberOfServers", "NumberOfRequests", "Lmbda", "Eta", "OptCost"];
-    if let Some(first) = results.first() {
-        let mut cost_names = first.alg_costs.iter().map(|(name, _)| name.as_ref()).collect();
-        headers.append(&mut cost_names);
-        wtr.write_record(headers)?;
-
-        for res in results.into_iter().progress_with(pb) {
+    /// Appends the results and flushes them, such that the file is complete up to the last instance.
+    pub fn write(&mut self, results: &[SimResult]) -> Result<(), Box<dyn Error>> {
+        if let Some(first) = results.first().filter(|_| !self.has_headers) {
+            let mut headers = vec![
+                "NumberOfServers",
+                "NumberOfRequests",
+                "Lmbda",
+                "Eta",
+                "OptCost",
+            ];
+            if self.seed.is_some() {
+                headers.push("Seed");
+            }
+            headers.extend(first.alg_costs.iter().map(|(name, _)| name.as_str()));
+            self.wtr.write_record(headers)?;
+            self.has_headers = true;
+        }
+        for res in results {
             let mut record = vec![
-                res.instance.k().to_string(), 
-                res.instance.length().to_string(),
+                res.k.to_string(),
+                res.length.to_string(),
                 res.lambda.to_string(),
                 res.eta.to_string(),
-                res.opt_cost.to_string()
-                ];
-                let mut cost_values = res.alg_costs.iter().map(|(_, value)| value.to_string()).collect();
-                record.append(&mut cost_values);
-
-            wtr.write_record(record)?;
+                res.opt_cost.to_string(),
+            ];
+            if let Some(seed) = self.seed {
+                record.push(seed.to_string());
+            }
+            record.extend(res.alg_costs.iter().map(|(_, value)| value.to_string()));
+            self.wtr.write_record(record)?;
         }
-    }
+        self.number_of_results += results.len();
+        self.wtr.flush()?;
 
-    
-    wtr.flush()?;
-    println!("{}", style("Exporting finished!").bold().green());
+        Ok(())
+    }
 
-    Ok(())
+    /// Flushes the file and returns the number of written results.
+    pub fn finish(mut self) -> Result<usize, Box<dyn Error>> {
+        self.wtr.flush()?;
+        println!("{}", style("Exporting finished!").bold().green());
+        Ok(self.number_of_results)
+    }
 }
@@ -1,3 +1,4 @@
+use samplelib::{stream_rng, RngStream};
 use serverlib::prelude::*;
 
 use console::style;
@@ -40,6 +41,18 @@ pub struct InstanceLoadConfig {
     pub directory: String,
 
     pub file_prefix: String,
+
+    /// Map the values onto a circle with this circumference instead of interpolating them to [min,max]
+    #[structopt(long)]
+    pub circle: Option<f32>,
+
+    /// Read `lat\tlon` pairs and scale them into the square [min,max]^2 of the Euclidean plane
+    #[structopt(long, conflicts_with = "circle")]
+    pub plane: bool,
+
+    /// Read instances on a weighted tree, see `parse_tree_instance` for the format
+    #[structopt(long, conflicts_with_all = &["circle", "plane"])]
+    pub tree: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -66,14 +79,19 @@ impl Error for InstanceError {
 #[allow(dead_code)]
 impl InstanceError {
     fn new(msg: String) -> InstanceError {
-        InstanceError { msg: msg }
+        InstanceError { msg }
     }
 }
 
+/// An instance together with a name which identifies it in checkpoints, i.e., its file name or index.
+pub type NamedInstance<M = Line> = (String, Instance<M>);
+
+/// Samples instances, where the `i`-th instance only depends on `seed` and `i` if a seed is given.
 pub fn generate_instances(
     sample_config: &InstanceSampleConfig,
     config: &InstanceConfig,
-) -> Result<Vec<Instance>, Box<dyn Error>> {
+    seed: Option<u64>,
+) -> Result<Vec<NamedInstance>, Box<dyn Error>> {
     println!("{}", style("Start generating instances...").bold().cyan());
     let number_of_instances = sample_config.number_of_instances;
     let pb = ProgressBar::new(number_of_instances as u64);
@@ -83,8 +101,10 @@ pub fn generate_instances(
     );
     let mut instances = Vec::with_capacity(number_of_instances);
 
-    for _ in (0..number_of_instances).progress_with(pb) {
-        instances.push(generate_instance(config, sample_config));
+    for i in (0..number_of_instances).progress_with(pb) {
+        let mut rng = stream_rng(seed, RngStream::Instance, i);
+        let instance = generate_instance(config, sample_config, &mut rng);
+        instances.push((format!("instance_{}", i), instance));
     }
     println!("{}", style("Finished generation!").bold().green());
     Ok(instances)
@@ -93,11 +113,51 @@ pub fn generate_instances(
 pub fn load_instances(
     load_config: &InstanceLoadConfig,
     config: &InstanceConfig,
-) -> Result<Vec<Instance>, Box<dyn Error>> {
+) -> Result<Vec<NamedInstance>, Box<dyn Error>> {
+    load_files(load_config, |path| load_instance(path, config))
+}
+
+/// Loads instances on the circle, see `InstanceLoadConfig::circle`.
+pub fn load_circle_instances(
+    load_config: &InstanceLoadConfig,
+    config: &InstanceConfig,
+) -> Result<Vec<NamedInstance<Circle>>, Box<dyn Error>> {
+    let circle =
+        Circle::new(load_config.circle.ok_or_else(|| {
+            InstanceError::new("No circumference for the circle given".to_string())
+        })?);
+    load_files(load_config, |path| {
+        load_circle_instance(path, config, circle)
+    })
+}
+
+/// Loads instances in the Euclidean plane, see `InstanceLoadConfig::plane`.
+pub fn load_plane_instances(
+    load_config: &InstanceLoadConfig,
+    config: &InstanceConfig,
+) -> Result<Vec<NamedInstance<Plane>>, Box<dyn Error>> {
+    load_files(load_config, |path| load_plane_instance(path, config))
+}
+
+/// Loads instances on weighted trees, see `InstanceLoadConfig::tree`.
+pub fn load_tree_instances(
+    load_config: &InstanceLoadConfig,
+    config: &InstanceConfig,
+) -> Result<Vec<NamedInstance<Tree>>, Box<dyn Error>> {
+    load_files(load_config, |path| load_tree_instance(path, config))
+}
+
+/// Loads the files with the prefix in the order of their names, which keeps the instance indices stable.
+fn load_files<M: Metric, F>(
+    load_config: &InstanceLoadConfig,
+    load: F,
+) -> Result<Vec<NamedInstance<M>>, Box<dyn Error>>
+where
+    F: Fn(&path::Path) -> Result<Instance<M>, Box<dyn Error>>,
+{
     println!("{}", style("Start loading instances...").bold().cyan());
 
-    let entries: Vec<path::PathBuf> = fs::read_dir(load_config.directory.clone())?
-        .into_iter()
+    let mut entries: Vec<path::PathBuf> = fs::read_dir(load_config.directory.clone())?
         .filter_map(Result::ok)
         .map(|r| r.path())
         .filter(|p| p.is_file())
@@ -109,6 +169,7 @@ pub fn load_instances(
                 .starts_with(&load_config.file_prefix)
         })
         .collect();
+    entries.sort();
 
     println!("Found {} files.", entries.len());
 
@@ -118,19 +179,21 @@ pub fn load_instances(
             .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] ({pos}/{len})"),
     );
 
-    let instances: Vec<Instance> = entries
+    let instances: Vec<NamedInstance<M>> = entries
         .into_iter()
         .progress_with(pb)
-        .map(|path| load_instance(&path.as_path(), &config))
-        .filter_map(Result::ok)
-        .collect::<Vec<Instance>>();
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().to_string();
+            load(path.as_path()).ok().map(|instance| (name, instance))
+        })
+        .collect::<Vec<NamedInstance<M>>>();
 
     println!("{}", style("Finished loading!").bold().green());
     if load_config.number_of_instances < 0 {
         return Ok(instances);
     }
 
-    let selected_instances: Vec<Instance> = instances
+    let selected_instances: Vec<NamedInstance<M>> = instances
         .into_iter()
         .take(load_config.number_of_instances as usize)
         .collect();
@@ -138,18 +201,21 @@ pub fn load_instances(
     Ok(selected_instances)
 }
 
-fn generate_instance(config: &InstanceConfig, sample_config: &InstanceSampleConfig) -> Instance {
-    let mut rng = rand::thread_rng();
+fn generate_instance<R: Rng>(
+    config: &InstanceConfig,
+    sample_config: &InstanceSampleConfig,
+    rng: &mut R,
+) -> Instance {
     let dist = Uniform::from(config.min_value..config.max_value);
 
     let mut requests: Vec<(i32, i32)> = vec![];
     let mut count = 0;
     while count < config.number_of_requests {
-        let s = dist.sample(&mut rng);
+        let s = dist.sample(rng);
         if sample_config.percentage_of_relocations > 0.0
             && rng.gen::<f32>() < sample_config.percentage_of_relocations
         {
-            let t = dist.sample(&mut rng);
+            let t = dist.sample(rng);
             requests.push((s, s));
             requests.push((s, t));
             count += 2;
@@ -158,18 +224,140 @@ fn generate_instance(config: &InstanceConfig, sample_config: &InstanceSampleConf
             count += 1;
         }
     }
-    let initial_pos: i32 = dist.sample(&mut rng);
+    let initial_pos: i32 = dist.sample(rng);
 
     let initial_positions: Vec<i32> = vec![initial_pos; config.number_of_servers];
     Instance::from((requests, initial_positions))
 }
 
-fn load_instance(path: &path::Path, config: &InstanceConfig) -> Result<Instance, Box<dyn Error>> {
+pub fn load_instance(
+    path: &path::Path,
+    config: &InstanceConfig,
+) -> Result<Instance, Box<dyn Error>> {
+    let raw_int_requests = read_values(path, config)?
+        .into_iter()
+        .map(|req| (req * 10000.0) as i32)
+        .collect::<Vec<i32>>();
+
+    let min_val = raw_int_requests.iter().min().unwrap();
+    let max_val = raw_int_requests.iter().max().unwrap();
+
+    let requests = raw_int_requests
+        .iter()
+        .map(|&req| interpolate(req, *min_val, *max_val, config.min_value, config.max_value).into())
+        .collect::<Vec<Request>>();
+
+    let initial_pos = (config.max_value - config.min_value) / 2;
+
+    Ok(Instance::new(
+        requests,
+        ServerConfiguration::from(vec![initial_pos; config.number_of_servers]),
+    ))
+}
+
+/// Loads an instance on the circle, where the values are taken modulo the circumference.
+///
+/// As on the line, all servers start in the middle between the smallest and the largest value.
+fn load_circle_instance(
+    path: &path::Path,
+    config: &InstanceConfig,
+    circle: Circle,
+) -> Result<Instance<Circle>, Box<dyn Error>> {
+    let values = read_values(path, config)?;
+    let (min_val, max_val) = values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &x| {
+            (min.min(x), max.max(x))
+        });
+    let requests = values
+        .into_iter()
+        .map(|req| Request::Simple(circle.point(req as f32)))
+        .collect::<Vec<Request<CirclePoint>>>();
+
+    let initial_pos = circle.point(((min_val + max_val) / 2.0) as f32);
+
+    Ok(Instance::with_metric(
+        circle,
+        requests,
+        ServerConfiguration::new(vec![initial_pos; config.number_of_servers]),
+    ))
+}
+
+/// Loads an instance in the plane from a file of `lat\tlon` pairs.
+///
+/// Both coordinates are scaled by the same factor, such that distances are preserved up to scaling.
+fn load_plane_instance(
+    path: &path::Path,
+    config: &InstanceConfig,
+) -> Result<Instance<Plane>, Box<dyn Error>> {
+    let file = fs::File::open(path)?;
+    let mut raw_requests: Vec<(f64, f64)> = io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| parse_lat_lon(&line))
+        .collect();
+
+    raw_requests.dedup();
+    if raw_requests.len() < config.number_of_requests {
+        return Err(InstanceError::new("Filtered instance is too short!".to_string()).into());
+    }
+    raw_requests.truncate(config.number_of_requests);
+
+    let bounds = |coordinate: fn(&(f64, f64)) -> f64| {
+        raw_requests
+            .iter()
+            .map(coordinate)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+                (min.min(x), max.max(x))
+            })
+    };
+    let (lat_min, lat_max) = bounds(|&(lat, _)| lat);
+    let (lon_min, lon_max) = bounds(|&(_, lon)| lon);
+    let extent = (lat_max - lat_min).max(lon_max - lon_min);
+    let scale = if extent > 0.0 {
+        (config.max_value - config.min_value) as f64 / extent
+    } else {
+        1.0
+    };
+    let min_value = config.min_value as f64;
+
+    let requests = raw_requests
+        .into_iter()
+        .map(|(lat, lon)| {
+            Request::Simple(PlanePoint::new(
+                (min_value + (lon - lon_min) * scale) as f32,
+                (min_value + (lat - lat_min) * scale) as f32,
+            ))
+        })
+        .collect::<Vec<Request<PlanePoint>>>();
+
+    let center = (config.min_value + config.max_value) as f32 / 2.0;
+
+    Ok(Instance::with_metric(
+        Plane,
+        requests,
+        ServerConfiguration::new(vec![
+            PlanePoint::new(center, center);
+            config.number_of_servers
+        ]),
+    ))
+}
+
+fn parse_lat_lon(line: &str) -> Option<(f64, f64)> {
+    let mut values = line.split_whitespace().map(|v| v.parse::<f64>());
+    match (values.next(), values.next(), values.next()) {
+        (Some(Ok(lat)), Some(Ok(lon)), None) => Some((lat, lon)),
+        _ => None,
+    }
+}
+
+/// Reads the first `number_of_requests` values of a file, skipping repetitions.
+fn read_values(path: &path::Path, config: &InstanceConfig) -> Result<Vec<f64>, Box<dyn Error>> {
     let file = fs::File::open(path)?;
 
     let mut raw_requests: Vec<f64> = io::BufReader::new(file)
         .lines()
-        .filter_map(Result::ok)
+        .map_while(Result::ok)
         .filter_map(|line| line.parse::<f64>().ok())
         //.filter(|req| req > &0.0)
         .collect();
@@ -179,25 +367,80 @@ fn load_instance(path: &path::Path, config: &InstanceConfig) -> Result<Instance,
         return Err(InstanceError::new("Filtered instance is too short!".to_string()).into());
     }
     raw_requests.resize_with(config.number_of_requests, || 1.0);
+    Ok(raw_requests)
+}
 
-    let raw_int_requests = raw_requests
-        .into_iter()
-        .map(|req| (req * 10000.0) as i32)
-        .collect::<Vec<i32>>();
+/// Loads an instance on a weighted tree from a file, see `parse_tree_instance` for the format.
+pub fn load_tree_instance(
+    path: &path::Path,
+    config: &InstanceConfig,
+) -> Result<Instance<Tree>, Box<dyn Error>> {
+    let file = fs::File::open(path)?;
+    parse_tree_instance(io::BufReader::new(file), config)
+}
 
-    let min_val = raw_int_requests.iter().min().unwrap();
-    let max_val = raw_int_requests.iter().max().unwrap();
+/// Parses an instance on a weighted tree with nodes `0..n`.
+///
+/// Every line is either an edge `e <u> <v> <weight>`, an initial server position `s <node>`,
+/// a request `r <node>` or a relocation request `r <from> <to>`. Empty lines and lines starting
+/// with `#` are ignored. Without server lines, all servers start at the root `0`. Otherwise, there
+/// have to be exactly `number_of_servers` server lines.
+pub fn parse_tree_instance<R: BufRead>(
+    reader: R,
+    config: &InstanceConfig,
+) -> Result<Instance<Tree>, Box<dyn Error>> {
+    let mut edges: Vec<(usize, usize, f32)> = vec![];
+    let mut servers: Vec<usize> = vec![];
+    let mut requests: Vec<Request<TreePoint>> = vec![];
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let invalid = || InstanceError::new(format!("Invalid line {}: {}", number + 1, line));
+        let node = |token: &str| token.parse::<usize>().map_err(|_| invalid());
+        match tokens.as_slice() {
+            [] => {}
+            [comment, ..] if comment.starts_with('#') => {}
+            ["e", u, v, w] => edges.push((node(u)?, node(v)?, w.parse().map_err(|_| invalid())?)),
+            ["s", u] => servers.push(node(u)?),
+            ["r", u] => requests.push(Request::Simple(node(u)?.into())),
+            ["r", u, v] => requests.push(Request::Relocation(node(u)?.into(), node(v)?.into())),
+            _ => return Err(invalid().into()),
+        }
+    }
 
-    let requests = raw_int_requests
+    let tree = Tree::from_edges(edges.len() + 1, &edges)?;
+    let n = tree.number_of_nodes();
+    if let Some(node) = servers
         .iter()
-        .map(|&req| interpolate(req, *min_val, *max_val, config.min_value, config.max_value).into())
-        .collect::<Vec<Request>>();
-
-    let initial_pos = (config.max_value - config.min_value) / 2;
+        .copied()
+        .chain(
+            requests
+                .iter()
+                .flat_map(|r| [r.pos().node(), r.end().node()]),
+        )
+        .find(|&node| node >= n)
+    {
+        return Err(InstanceError::new(format!("Unknown node {}", node)).into());
+    }
+    if requests.len() < config.number_of_requests {
+        return Err(InstanceError::new("Tree instance is too short!".to_string()).into());
+    }
+    requests.truncate(config.number_of_requests);
+    if servers.is_empty() {
+        servers = vec![0; config.number_of_servers];
+    } else if servers.len() != config.number_of_servers {
+        return Err(InstanceError::new(format!(
+            "Tree instance has {} servers, but k = {}",
+            servers.len(),
+            config.number_of_servers
+        ))
+        .into());
+    }
 
-    Ok(Instance::new(
+    Ok(Instance::with_metric(
+        tree,
         requests,
-        ServerConfiguration::from(vec![initial_pos; config.number_of_servers]),
+        ServerConfiguration::new(servers.into_iter().map(TreePoint::from).collect()),
     ))
 }
 
@@ -215,4 +458,113 @@ mod tests {
         assert_eq!(10, interpolate(20, 0, 100, 0, 50));
         assert_eq!(20, interpolate(120, 100, 200, 10, 60));
     }
+
+    #[test]
+    fn generate_instances_is_reproducible() -> Result<(), Box<dyn Error>> {
+        let config = InstanceConfig {
+            number_of_servers: 2,
+            number_of_requests: 20,
+            min_value: 0,
+            max_value: 100,
+        };
+        let sample_config = InstanceSampleConfig {
+            number_of_instances: 3,
+            percentage_of_relocations: 0.5,
+        };
+        let first = generate_instances(&sample_config, &config, Some(7))?;
+        let second = generate_instances(&sample_config, &config, Some(7))?;
+        assert_eq!(format!("{:?}", first), format!("{:?}", second));
+        // every instance has its own stream
+        assert_ne!(format!("{:?}", first[0]), format!("{:?}", first[1]));
+        Ok(())
+    }
+
+    #[test]
+    fn load_circle_instance_wraps_values() -> Result<(), Box<dyn Error>> {
+        let config = InstanceConfig {
+            number_of_servers: 2,
+            number_of_requests: 3,
+            min_value: 0,
+            max_value: 100,
+        };
+        let path = std::env::temp_dir().join("serversim_circle_instance.txt");
+        fs::write(&path, "7.5\n25\n-1\n30\n")?;
+        let instance = load_circle_instance(&path, &config, Circle::new(24.0))?;
+        fs::remove_file(&path)?;
+        assert_eq!(
+            vec![7.5, 1.0, 23.0],
+            instance
+                .requests()
+                .iter()
+                .map(|r| r.pos().position())
+                .collect::<Vec<f32>>()
+        );
+        // the servers start in the middle of the values -1 and 25
+        assert_eq!(
+            &ServerConfiguration::new(vec![Circle::new(24.0).point(12.0); 2]),
+            instance.initial_positions()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn load_plane_instance_scales_coordinates() -> Result<(), Box<dyn Error>> {
+        let config = InstanceConfig {
+            number_of_servers: 2,
+            number_of_requests: 3,
+            min_value: 0,
+            max_value: 100,
+        };
+        let path = std::env::temp_dir().join("serversim_plane_instance.txt");
+        fs::write(
+            &path,
+            "10.0\t20.0\n10.0\t20.0\n12.0\t24.0\ninvalid\n11.0\t20.0\n",
+        )?;
+        let instance = load_plane_instance(&path, &config)?;
+        fs::remove_file(&path)?;
+        assert_eq!(
+            vec![(0.0, 0.0), (100.0, 50.0), (0.0, 25.0)],
+            instance
+                .requests()
+                .iter()
+                .map(|r| (r.pos().x(), r.pos().y()))
+                .collect::<Vec<(f32, f32)>>()
+        );
+        assert_eq!(
+            &ServerConfiguration::new(vec![PlanePoint::new(50.0, 50.0); 2]),
+            instance.initial_positions()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_tree_instance_works() -> Result<(), Box<dyn Error>> {
+        let config = InstanceConfig {
+            number_of_servers: 2,
+            number_of_requests: 2,
+            min_value: 0,
+            max_value: 100,
+        };
+        let input = "# star\ne 0 1 2.5\ne 2 0 1\n\nr 1\nr 2 1\nr 0\n";
+        let instance = parse_tree_instance(input.as_bytes(), &config)?;
+        assert_eq!(2, instance.length());
+        assert_eq!(
+            &ServerConfiguration::new(vec![TreePoint::from(0); 2]),
+            instance.initial_positions()
+        );
+        assert_eq!(3.5, instance.metric().distance(&1.into(), &2.into()));
+
+        assert!(parse_tree_instance("e 0 1 1\nr 2\nr 1\n".as_bytes(), &config).is_err());
+        assert!(parse_tree_instance("e 0 1\nr 1\nr 1\n".as_bytes(), &config).is_err());
+        assert!(parse_tree_instance("e 0 1 1\nr 1\n".as_bytes(), &config).is_err());
+
+        // the server lines have to match the number of servers
+        let instance = parse_tree_instance("e 0 1 1\ns 1\ns 0\nr 1\nr 0\n".as_bytes(), &config)?;
+        assert_eq!(
+            &ServerConfiguration::new(vec![TreePoint::from(1), TreePoint::from(0)]),
+            instance.initial_positions()
+        );
+        assert!(parse_tree_instance("e 0 1 1\ns 1\nr 1\nr 0\n".as_bytes(), &config).is_err());
+        Ok(())
+    }
 }
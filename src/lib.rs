@@ -1,7 +1,16 @@
+pub mod checkpoint;
 pub mod cli;
+pub mod dp_solver;
+pub mod experiment;
 pub mod export;
 pub mod instance_generator;
+pub mod opt_cache;
+pub mod pipeline;
+pub mod plot;
 pub mod pred_generator;
 pub mod sample_generator;
 pub mod sim;
 pub mod solver;
+pub mod taxi_solver;
+pub mod trace;
+pub mod visualize;
@@ -0,0 +1,318 @@
+use crate::solver::{SolverError, SolverType};
+use serverlib::prelude::*;
+
+use std::convert::TryInto;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 4] = b"OPT1";
+const FNV_OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+/// Metric spaces whose points can be stored in the cache as a fixed number of coordinates.
+pub trait CacheMetric: Metric {
+    const DIMENSION: usize;
+
+    fn coordinates(&self, point: &Self::Point) -> Vec<f32>;
+
+    fn point_at(&self, coordinates: &[f32]) -> Self::Point;
+}
+
+impl CacheMetric for Line {
+    const DIMENSION: usize = 1;
+
+    fn coordinates(&self, point: &f32) -> Vec<f32> {
+        vec![*point]
+    }
+
+    fn point_at(&self, coordinates: &[f32]) -> f32 {
+        coordinates[0]
+    }
+}
+
+impl CacheMetric for Circle {
+    const DIMENSION: usize = 1;
+
+    fn coordinates(&self, point: &CirclePoint) -> Vec<f32> {
+        vec![point.position()]
+    }
+
+    fn point_at(&self, coordinates: &[f32]) -> CirclePoint {
+        self.point(coordinates[0])
+    }
+}
+
+impl CacheMetric for Plane {
+    const DIMENSION: usize = 2;
+
+    fn coordinates(&self, point: &PlanePoint) -> Vec<f32> {
+        vec![point.x(), point.y()]
+    }
+
+    fn point_at(&self, coordinates: &[f32]) -> PlanePoint {
+        PlanePoint::new(coordinates[0], coordinates[1])
+    }
+}
+
+/// Points of a tree are stored as their node and their distance to it.
+impl CacheMetric for Tree {
+    const DIMENSION: usize = 2;
+
+    fn coordinates(&self, point: &TreePoint) -> Vec<f32> {
+        vec![point.node() as f32, point.up()]
+    }
+
+    fn point_at(&self, coordinates: &[f32]) -> TreePoint {
+        self.point(coordinates[0] as usize, coordinates[1])
+    }
+}
+
+/// A content-addressed cache of optimal solutions on disk.
+///
+/// The solution of an instance is stored in a file named by a 128-bit FNV-1a hash of the instance, the
+/// solver and the cost model. The file contains the cost of OPT followed by all configurations of the
+/// schedule as little-endian floats. Files which do not match the instance are treated as missing.
+#[derive(Debug, Clone)]
+pub struct OptCache {
+    dir: PathBuf,
+}
+
+impl OptCache {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<OptCache, std::io::Error> {
+        fs::create_dir_all(&dir)?;
+        Ok(OptCache {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    fn path<M: Metric>(
+        &self,
+        instance: &Instance<M>,
+        solver: SolverType,
+        model: CostModel,
+    ) -> PathBuf {
+        // the debug representation of floats is exact, hence it identifies the instance
+        let key = format!("{:?}{:?}{:?}", instance, solver, model);
+        self.dir.join(format!("{:032x}.opt", fnv1a(key.as_bytes())))
+    }
+
+    /// Returns the cached solution of the instance, if there is one.
+    pub fn get<M: CacheMetric>(
+        &self,
+        instance: &Instance<M>,
+        solver: SolverType,
+        model: CostModel,
+    ) -> Option<(Schedule<M::Point>, f64)> {
+        let bytes = fs::read(self.path(instance, solver, model)).ok()?;
+        decode(instance, &bytes)
+    }
+
+    /// Stores the solution of the instance.
+    pub fn put<M: CacheMetric>(
+        &self,
+        instance: &Instance<M>,
+        solver: SolverType,
+        model: CostModel,
+        solution: &(Schedule<M::Point>, f64),
+    ) -> Result<(), std::io::Error> {
+        let path = self.path(instance, solver, model);
+        // several workers may solve the same instance, hence the file is replaced atomically
+        let tmp = path.with_extension(format!("{}.tmp", rand::random::<u64>()));
+        fs::write(&tmp, encode(instance, solution))?;
+        fs::rename(&tmp, &path)
+    }
+
+    /// Returns the cached solution of the instance, or computes it by `solve` and caches it.
+    ///
+    /// A solution which cannot be cached is still returned, since solving is more expensive than a cache miss.
+    pub fn solve<M: CacheMetric, F>(
+        &self,
+        instance: &Instance<M>,
+        solver: SolverType,
+        model: CostModel,
+        solve: F,
+    ) -> Result<(Schedule<M::Point>, f64), SolverError>
+    where
+        F: FnOnce() -> Result<(Schedule<M::Point>, f64), SolverError>,
+    {
+        if let Some(solution) = self.get(instance, solver, model) {
+            return Ok(solution);
+        }
+        let solution = solve()?;
+        if let Err(e) = self.put(instance, solver, model, &solution) {
+            eprintln!("Warning: cannot write the OPT cache: {}", e);
+        }
+        Ok(solution)
+    }
+}
+
+/// The 128-bit FNV-1a hash, which is stable across runs and platforms.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u128 {
+    bytes.iter().fold(FNV_OFFSET, |hash, &byte| {
+        (hash ^ byte as u128).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Solves the instance by `solve`, or looks up its solution if a cache is given.
+pub fn solve_cached<M: CacheMetric, F>(
+    cache: Option<&OptCache>,
+    instance: &Instance<M>,
+    solver: SolverType,
+    model: CostModel,
+    solve: F,
+) -> Result<(Schedule<M::Point>, f64), SolverError>
+where
+    F: FnOnce() -> Result<(Schedule<M::Point>, f64), SolverError>,
+{
+    match cache {
+        Some(cache) => cache.solve(instance, solver, model, solve),
+        None => solve(),
+    }
+}
+
+fn encode<M: CacheMetric>(
+    instance: &Instance<M>,
+    (schedule, cost): &(Schedule<M::Point>, f64),
+) -> Vec<u8> {
+    let metric = instance.metric();
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend(&cost.to_le_bytes());
+    bytes.extend(&(schedule.len() as u32).to_le_bytes());
+    bytes.extend(&(instance.k() as u32).to_le_bytes());
+    for config in schedule {
+        for point in config {
+            for x in metric.coordinates(point) {
+                bytes.extend(&x.to_le_bytes());
+            }
+        }
+    }
+    bytes
+}
+
+fn decode<M: CacheMetric>(
+    instance: &Instance<M>,
+    bytes: &[u8],
+) -> Option<(Schedule<M::Point>, f64)> {
+    let (magic, bytes) = bytes.split_at_checked(MAGIC.len())?;
+    let (cost, bytes) = bytes.split_at_checked(8)?;
+    let (len, bytes) = bytes.split_at_checked(4)?;
+    let (k, bytes) = bytes.split_at_checked(4)?;
+    let len = u32::from_le_bytes(len.try_into().ok()?) as usize;
+    let k = u32::from_le_bytes(k.try_into().ok()?) as usize;
+    if magic != MAGIC
+        || len != instance.length() + 1
+        || k != instance.k()
+        || bytes.len() != len * k * M::DIMENSION * 4
+    {
+        return None;
+    }
+
+    let metric = instance.metric();
+    let coordinates: Vec<f32> = bytes
+        .chunks_exact(4)
+        .map(|x| f32::from_le_bytes(x.try_into().unwrap()))
+        .collect();
+    let configs = coordinates
+        .chunks_exact(k * M::DIMENSION)
+        .map(|config| {
+            ServerConfiguration::new(
+                config
+                    .chunks_exact(M::DIMENSION)
+                    .map(|point| metric.point_at(point))
+                    .collect(),
+            )
+        })
+        .collect::<Vec<_>>();
+    Some((
+        Schedule::from(configs),
+        f64::from_le_bytes(cost.try_into().ok()?),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opt_cache_works() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join("serversim_opt_cache_test");
+        let _ = fs::remove_dir_all(&dir);
+        let cache = OptCache::new(&dir)?;
+        let instance = Instance::from((vec![20, 80, 30], vec![50, 50]));
+        let solution = (
+            Schedule::from(vec![vec![50, 50], vec![20, 50], vec![20, 80], vec![30, 80]]),
+            70.0,
+        );
+        let model = CostModel::Easy;
+
+        assert!(cache
+            .get(&instance, SolverType::MinCostFlow, model)
+            .is_none());
+        let solved = cache.solve(&instance, SolverType::MinCostFlow, model, || {
+            Ok(solution.clone())
+        })?;
+        assert_eq!(solution, solved);
+        // the second lookup must not solve again
+        let cached = cache.solve(&instance, SolverType::MinCostFlow, model, || {
+            Err(SolverError::new("solved twice".to_string()))
+        })?;
+        assert_eq!(solution, cached);
+        assert!(cache
+            .get(&instance, SolverType::MinCostFlow, CostModel::Hard)
+            .is_none());
+        let other = Instance::from((vec![20, 80, 31], vec![50, 50]));
+        assert!(cache.get(&other, SolverType::MinCostFlow, model).is_none());
+
+        let plane = Instance::with_metric(
+            Plane,
+            vec![Request::Simple(PlanePoint::new(1.0, 2.0))],
+            ServerConfiguration::new(vec![PlanePoint::new(0.0, 0.0)]),
+        );
+        let solution = (
+            Schedule::from(vec![
+                ServerConfiguration::new(vec![PlanePoint::new(0.0, 0.0)]),
+                ServerConfiguration::new(vec![PlanePoint::new(1.0, 2.0)]),
+            ]),
+            5f64.sqrt(),
+        );
+        cache.put(&plane, SolverType::MinCostFlow, model, &solution)?;
+        assert_eq!(
+            Some(solution.clone()),
+            cache.get(&plane, SolverType::MinCostFlow, model)
+        );
+
+        // points of a tree keep their position on the edge
+        let tree = Tree::from_edges(3, &[(0, 1, 2.0), (0, 2, 3.0)])?;
+        let point = tree.point(2, 1.5);
+        let tree_instance = Instance::with_metric(
+            tree,
+            vec![Request::Simple(point)],
+            ServerConfiguration::new(vec![TreePoint::from(1)]),
+        );
+        let tree_solution = (
+            Schedule::from(vec![
+                ServerConfiguration::new(vec![TreePoint::from(1)]),
+                ServerConfiguration::new(vec![point]),
+            ]),
+            3.5,
+        );
+        cache.put(
+            &tree_instance,
+            SolverType::MinCostFlow,
+            model,
+            &tree_solution,
+        )?;
+        assert_eq!(
+            Some(tree_solution),
+            cache.get(&tree_instance, SolverType::MinCostFlow, model)
+        );
+
+        // a cache which cannot be written does not discard the solution
+        fs::remove_dir_all(&dir)?;
+        let solved = cache.solve(&plane, SolverType::MinCostFlow, CostModel::Hard, || {
+            Ok(solution.clone())
+        })?;
+        assert_eq!(solution, solved);
+        Ok(())
+    }
+}
@@ -0,0 +1,670 @@
+use crate::checkpoint::Checkpoint;
+use crate::export::{ExportConfig, Exporter};
+use crate::instance_generator::NamedInstance;
+use crate::opt_cache::OptCache;
+use crate::pred_generator::predict_sample;
+use crate::sample_generator::{IndexedSample, SampleConfig};
+use crate::sim::{self, Simulate, SimulatorError, Simulators};
+use crate::solver::SampleBuilder;
+use samplelib::*;
+use serverlib::prelude::{Instance, Metric};
+
+use console::style;
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Condvar, Mutex};
+use std::thread;
+
+/// What became of a single instance.
+enum Outcome {
+    Unsolved,
+    NoPredictions,
+    Simulated(Result<Vec<SimResult>, SimulatorError>),
+}
+
+/// The configuration shared by all instances of a run.
+struct Pipeline<'a> {
+    sample_config: &'a SampleConfig,
+    cache: Option<OptCache>,
+    simulator: &'a Simulators,
+    lambdas: Vec<f32>,
+    debug: bool,
+    seed: Option<u64>,
+}
+
+impl<'a> Pipeline<'a> {
+    /// Solves the `index`-th instance, generates its predictions and simulates them.
+    fn process<M: Metric>(&self, index: usize, instance: Instance<M>) -> Outcome
+    where
+        Instance<M>: SampleBuilder<M>,
+        Sample<M>: Simulate<M>,
+    {
+        let sample = match instance.build_sample(
+            self.sample_config.solver,
+            self.sample_config.cost_model,
+            self.cache.as_ref(),
+        ) {
+            Ok(sample) => sample,
+            Err(_) => return Outcome::Unsolved,
+        };
+        let sample = match predict_sample(sample, &self.sample_config.pred_config, self.seed, index)
+        {
+            Ok(sample) => sample,
+            Err(_) => return Outcome::NoPredictions,
+        };
+        let mut rng = stream_rng(self.seed, RngStream::Simulation, index);
+        Outcome::Simulated(sim::simulate_sample(
+            sample,
+            &self.lambdas,
+            self.simulator,
+            self.debug,
+            &mut rng,
+        ))
+    }
+}
+
+/// Solves, predicts and simulates the instances in parallel and exports the results of every instance as
+/// soon as it is finished, such that only the instances in progress are kept in memory.
+///
+/// The results are written in the order of the instances, hence the output is reproducible if a seed is given.
+/// With a checkpoint, the completed instances are skipped and every exported instance is recorded. Instances
+/// without results, i.e. unsolved, rejected or failed ones, are not recorded, hence a resumed run retries them.
+pub fn run<M: Metric + Send>(
+    instances: Vec<NamedInstance<M>>,
+    sample_config: &SampleConfig,
+    simulator: &Simulators,
+    export_config: &ExportConfig,
+    debug: bool,
+    seed: Option<u64>,
+    checkpoint: Option<Checkpoint>,
+) -> Result<(), Box<dyn Error>>
+where
+    M::Point: Send,
+    Instance<M>: SampleBuilder<M>,
+    Sample<M>: Simulate<M>,
+{
+    let pipeline = Pipeline {
+        sample_config,
+        cache: sample_config.open_cache()?,
+        simulator,
+        lambdas: sim::lambdas(simulator),
+        debug,
+        seed,
+    };
+    let instances = instances
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, instance))| (index, name, instance))
+        .collect();
+    stream(
+        instances,
+        |index, instance| pipeline.process(index, instance),
+        export_config,
+        debug,
+        seed,
+        checkpoint,
+    )
+}
+
+/// Simulates samples with their saved predictions like `run` simulates their instances.
+pub fn run_samples<M: Metric + Send>(
+    samples: Vec<IndexedSample<M>>,
+    simulator: &Simulators,
+    export_config: &ExportConfig,
+    debug: bool,
+    seed: Option<u64>,
+    checkpoint: Option<Checkpoint>,
+) -> Result<(), Box<dyn Error>>
+where
+    M::Point: Send,
+    Sample<M>: Simulate<M>,
+{
+    let lambdas = sim::lambdas(simulator);
+    stream(
+        samples,
+        |index, sample| {
+            let mut rng = stream_rng(seed, RngStream::Simulation, index);
+            Outcome::Simulated(sim::simulate_sample(
+                sample, &lambdas, simulator, debug, &mut rng,
+            ))
+        },
+        export_config,
+        debug,
+        seed,
+        checkpoint,
+    )
+}
+
+/// Processes the indexed and named items in parallel and exports their results in order, see `run`.
+fn stream<T: Send, F>(
+    items: Vec<(usize, String, T)>,
+    process: F,
+    export_config: &ExportConfig,
+    debug: bool,
+    seed: Option<u64>,
+    mut checkpoint: Option<Checkpoint>,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(usize, T) -> Outcome + Sync,
+{
+    println!("{}", style("Start simulating...").bold().cyan());
+    let number_of_items = items.len();
+    // the index of an item determines its random streams, hence skipped items keep their index
+    let (names, items): (Vec<String>, Vec<(usize, T)>) = items
+        .into_iter()
+        .filter(|(_, name, _)| !checkpoint.as_ref().is_some_and(|c| c.is_completed(name)))
+        .map(|(index, name, item)| (name, (index, item)))
+        .unzip();
+    if items.len() < number_of_items {
+        println!(
+            "Skipping {} completed instances.",
+            style(number_of_items - items.len()).bold().green()
+        );
+    }
+    let pb = ProgressBar::new(items.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] ({pos}/{len})"),
+    );
+    let offset = checkpoint.as_ref().map_or(0, Checkpoint::offset);
+    let mut exporter = Exporter::new(export_config, seed, offset)?;
+
+    let (mut unsolved, mut rejected, mut failed) = (0, 0, 0);
+    let mut first_error = None;
+    // a little slack per thread keeps the workers busy while the next item to export is still running
+    let window = 2 * rayon::current_num_threads();
+    process_in_order(
+        items,
+        window,
+        |(index, item)| process(index, item),
+        |position, outcome| {
+            pb.inc(1);
+            match outcome {
+                Outcome::Unsolved => unsolved += 1,
+                Outcome::NoPredictions => rejected += 1,
+                Outcome::Simulated(Ok(results)) => {
+                    exporter.write(&results)?;
+                    if let Some(checkpoint) = checkpoint.as_mut() {
+                        checkpoint.complete(&names[position], exporter.size()?)?;
+                    }
+                }
+                Outcome::Simulated(Err(e)) => {
+                    if debug {
+                        println!("{}", e);
+                    }
+                    first_error.get_or_insert(e);
+                    failed += 1;
+                }
+            }
+            Ok::<_, Box<dyn Error>>(())
+        },
+    )?;
+    pb.finish();
+
+    if unsolved > 0 {
+        println!(
+            "{} instances could not be solved!",
+            style(unsolved).bold().red()
+        );
+    }
+    if rejected > 0 {
+        println!(
+            "{} samples have been rejected because no valid predictions have been found!",
+            style(rejected).bold().red()
+        );
+    }
+    if failed > 0 {
+        println!(
+            "{} {}",
+            style("Samples with invalid simulation: ").bold().green(),
+            style(failed).bold().red()
+        );
+    }
+    if let Some(e) = first_error {
+        println!("{} {}", style("First simulation error:").bold().red(), e);
+    }
+    let number_of_results = exporter.finish()?;
+    println!("{}", style("Simulation finished!").bold().green());
+    println!(
+        "{} {}",
+        style("Number of results: ").bold().green(),
+        style(number_of_results).bold().red()
+    );
+
+    Ok(())
+}
+
+/// Processes the items in parallel and consumes their outcomes in the order of the items.
+///
+/// The workers take the items in order and wait while they are `window` or more items ahead of
+/// the consumer, hence at most `window` outcomes are buffered no matter how long a single item takes.
+/// Stops at the first error of `consume` and returns the largest number of buffered outcomes otherwise.
+fn process_in_order<T: Send, O: Send, E>(
+    items: Vec<T>,
+    window: usize,
+    process: impl Fn(T) -> O + Sync,
+    mut consume: impl FnMut(usize, O) -> Result<(), E>,
+) -> Result<usize, E> {
+    // the position of the next outcome to consume, or `None` once consuming failed
+    let progress = (Mutex::new(Some(0)), Condvar::new());
+    let advance = |next: Option<usize>| {
+        let (lock, cvar) = &progress;
+        *lock.lock().unwrap() = next;
+        cvar.notify_all();
+    };
+
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        let (process, progress, advance) = (&process, &progress, &advance);
+        scope.spawn(move || {
+            // the workers stop once consuming failed, in which case sending fails as well
+            let _ = items
+                .into_iter()
+                .enumerate()
+                .par_bridge()
+                .try_for_each_with(tx, |tx, (position, item)| {
+                    let (lock, cvar) = progress;
+                    let next = *cvar
+                        .wait_while(lock.lock().unwrap(), |next| {
+                            next.is_some_and(|next| position >= next + window)
+                        })
+                        .unwrap();
+                    next.ok_or(())?;
+                    // a panicking item would otherwise leave the other workers waiting for it
+                    let outcome = panic::catch_unwind(AssertUnwindSafe(|| process(item)))
+                        .unwrap_or_else(|e| {
+                            advance(None);
+                            panic::resume_unwind(e)
+                        });
+                    tx.send((position, outcome)).map_err(|_| ())
+                });
+        });
+
+        let mut pending = BTreeMap::new();
+        let mut max_pending = 0;
+        let mut next = 0;
+        for (position, outcome) in rx {
+            pending.insert(position, outcome);
+            max_pending = max_pending.max(pending.len());
+            while let Some(outcome) = pending.remove(&next) {
+                if let Err(e) = consume(next, outcome) {
+                    advance(None);
+                    return Err(e);
+                }
+                next += 1;
+                advance(Some(next));
+            }
+        }
+        Ok(max_pending)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint;
+    use crate::instance_generator::{
+        generate_instances, load_tree_instances, InstanceConfig, InstanceLoadConfig,
+        InstanceSampleConfig,
+    };
+    use crate::sample_generator;
+    use std::fs;
+    use structopt::StructOpt;
+
+    fn simulate(export_config: &ExportConfig, seed: Option<u64>) -> Result<String, Box<dyn Error>> {
+        let config = InstanceConfig {
+            number_of_servers: 2,
+            number_of_requests: 20,
+            min_value: 0,
+            max_value: 100,
+        };
+        let generator_config = InstanceSampleConfig {
+            number_of_instances: 6,
+            percentage_of_relocations: 0.0,
+        };
+        let sample_config = SampleConfig::from_iter(&["test", "-p", "3", "-s", "20"]);
+        let simulator = Simulators::from_iter(&["test", "kserver", "--lambdas", "2"]);
+
+        let checkpoint = checkpoint::open(export_config, seed)?;
+        let seed = checkpoint.as_ref().map_or(seed, |c| Some(c.seed()));
+        let instances = generate_instances(&generator_config, &config, seed)?;
+        run(
+            instances,
+            &sample_config,
+            &simulator,
+            export_config,
+            false,
+            seed,
+            checkpoint,
+        )?;
+        Ok(fs::read_to_string(&export_config.output_file)?)
+    }
+
+    #[test]
+    fn pipeline_exports_in_instance_order() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join("serversim_pipeline.csv");
+        let export_config = ExportConfig {
+            output_file: path.to_string_lossy().to_string(),
+            checkpoint: None,
+            resume: false,
+        };
+        let first = simulate(&export_config, Some(3))?;
+        assert_eq!(first, simulate(&export_config, Some(3))?);
+        fs::remove_file(&path)?;
+
+        let mut lines = first.lines();
+        assert!(lines.next().unwrap().starts_with("NumberOfServers"));
+        // two lambdas for every prediction of every instance
+        assert_eq!(0, lines.count() % 2);
+        Ok(())
+    }
+
+    #[test]
+    fn resume_retries_instances_without_results() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join("serversim_pipeline_retry");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        let mut export_config = ExportConfig {
+            output_file: dir.join("result.csv").to_string_lossy().to_string(),
+            checkpoint: Some(dir.to_string_lossy().to_string()),
+            resume: false,
+        };
+        let items = || {
+            ["solved", "unsolved", "rejected", "failed"]
+                .iter()
+                .enumerate()
+                .map(|(index, name)| (index, name.to_string(), *name))
+                .collect()
+        };
+        let checkpoint = checkpoint::open(&export_config, Some(0))?;
+        stream(
+            items(),
+            |_, name| match name {
+                "unsolved" => Outcome::Unsolved,
+                "rejected" => Outcome::NoPredictions,
+                "failed" => Outcome::Simulated(Err(SimulatorError::new("failed".to_string()))),
+                _ => Outcome::Simulated(Ok(vec![])),
+            },
+            &export_config,
+            false,
+            Some(0),
+            checkpoint,
+        )?;
+
+        export_config.resume = true;
+        let retried = Mutex::new(Vec::new());
+        let checkpoint = checkpoint::open(&export_config, Some(0))?;
+        stream(
+            items(),
+            |_, name| {
+                retried.lock().unwrap().push(name);
+                Outcome::Simulated(Ok(vec![]))
+            },
+            &export_config,
+            false,
+            Some(0),
+            checkpoint,
+        )?;
+        let mut retried = retried.into_inner()?;
+        retried.sort();
+        assert_eq!(vec!["failed", "rejected", "unsolved"], retried);
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn slow_items_buffer_at_most_a_window_of_outcomes() {
+        let mut consumed = Vec::new();
+        let max_pending = process_in_order(
+            (0..200).collect(),
+            3,
+            |item: usize| {
+                if item == 0 {
+                    thread::sleep(std::time::Duration::from_millis(100));
+                }
+                item
+            },
+            |position, outcome| {
+                assert_eq!(position, outcome);
+                consumed.push(outcome);
+                Ok::<_, ()>(())
+            },
+        );
+        assert!(max_pending.unwrap() <= 3);
+        assert_eq!((0..200).collect::<Vec<_>>(), consumed);
+    }
+
+    #[test]
+    fn processing_stops_at_the_first_consumer_error() {
+        let processed = std::sync::atomic::AtomicUsize::new(0);
+        let result = process_in_order(
+            (0..200).collect(),
+            3,
+            |item: usize| {
+                processed.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                item
+            },
+            |position, _| {
+                if position == 10 {
+                    Err(position)
+                } else {
+                    Ok(())
+                }
+            },
+        );
+        assert_eq!(Err(10), result);
+        assert!(processed.into_inner() < 200);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panicking_items_do_not_block_the_other_workers() {
+        let _ = process_in_order(
+            (0..200).collect(),
+            3,
+            |item: usize| {
+                if item == 5 {
+                    panic!("item {}", item);
+                }
+                item
+            },
+            |_, _| Ok::<_, ()>(()),
+        );
+    }
+
+    #[test]
+    fn pipeline_resumes_from_checkpoint() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join("serversim_pipeline_checkpoint");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        let mut export_config = ExportConfig {
+            output_file: dir.join("result.csv").to_string_lossy().to_string(),
+            checkpoint: Some(dir.to_string_lossy().to_string()),
+            resume: false,
+        };
+        let complete = simulate(&export_config, None)?;
+
+        // interrupt the run after the second instance, while the third is exported
+        let checkpoint_file = fs::read_dir(&dir)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "checkpoint"))
+            .unwrap();
+        let recorded = fs::read_to_string(&checkpoint_file)?;
+        let lines: Vec<&str> = recorded.lines().collect();
+        fs::write(
+            &checkpoint_file,
+            format!("{}\n{}\n{}\n12", lines[0], lines[1], lines[2]),
+        )?;
+        let mut output = fs::read(&export_config.output_file)?;
+        output.truncate(lines[3].split('\t').next().unwrap().parse::<usize>()? - 5);
+        fs::write(&export_config.output_file, output)?;
+
+        export_config.resume = true;
+        assert_eq!(complete, simulate(&export_config, None)?);
+        assert_eq!(recorded, fs::read_to_string(&checkpoint_file)?);
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn ktaxi_requires_general_for_more_servers() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join("serversim_pipeline_ktaxi.csv");
+        let export_config = ExportConfig {
+            output_file: path.to_string_lossy().to_string(),
+            checkpoint: None,
+            resume: false,
+        };
+        let config = InstanceConfig {
+            number_of_servers: 3,
+            number_of_requests: 10,
+            min_value: 0,
+            max_value: 100,
+        };
+        let generator_config = InstanceSampleConfig {
+            number_of_instances: 2,
+            percentage_of_relocations: 0.5,
+        };
+        let sample_config = SampleConfig::from_iter(&["test", "-p", "2", "-s", "20"]);
+        let instances = generate_instances(&generator_config, &config, Some(5))?;
+
+        let simulator = Simulators::from_iter(&["test", "ktaxi", "--lambdas", "2"]);
+        let error = sim::check_instances(&simulator, 3, 10).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("BiasedDC requires exactly 2 servers"));
+        assert!(sim::check_instances(&simulator, 2, 10).is_ok());
+
+        let simulator = Simulators::from_iter(&["test", "ktaxi", "--lambdas", "2", "--general"]);
+        run(
+            instances,
+            &sample_config,
+            &simulator,
+            &export_config,
+            false,
+            Some(5),
+            None,
+        )?;
+        let header = fs::read_to_string(&path)?
+            .lines()
+            .next()
+            .unwrap()
+            .to_string();
+        assert!(header.contains("KBDC") && header.contains("KLBDC"));
+        assert!(!header.contains(",BDC"));
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn tree_instances_are_simulated() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join("serversim_pipeline_trees");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        let edges = "e 1 0 2\ne 2 0 3\ne 3 1 1\ne 4 1 4\n";
+        fs::write(
+            dir.join("tree_a.txt"),
+            format!("{}r 3\nr 2\nr 4\nr 0\n", edges),
+        )?;
+        fs::write(
+            dir.join("tree_b.txt"),
+            format!("{}s 4\ns 2\nr 1\nr 3\nr 0\nr 2\n", edges),
+        )?;
+        let config = InstanceConfig {
+            number_of_servers: 2,
+            number_of_requests: 4,
+            min_value: 0,
+            max_value: 100,
+        };
+        let load_config = InstanceLoadConfig::from_iter(&[
+            "test",
+            "tree",
+            "-d",
+            &dir.to_string_lossy(),
+            "--tree",
+        ]);
+        let instances = load_tree_instances(&load_config, &config)?;
+        assert_eq!(2, instances.len());
+
+        let export_config = ExportConfig {
+            output_file: dir.join("result.csv").to_string_lossy().to_string(),
+            checkpoint: None,
+            resume: false,
+        };
+        let sample_config = SampleConfig::from_iter(&["test", "-p", "2", "-b", "100", "-m", "1"]);
+        let simulator = Simulators::from_iter(&["test", "kserver", "--lambdas", "2"]);
+        run(
+            instances,
+            &sample_config,
+            &simulator,
+            &export_config,
+            false,
+            Some(1),
+            None,
+        )?;
+        let output = fs::read_to_string(&export_config.output_file)?;
+        let mut lines = output.lines();
+        assert!(lines.next().unwrap().ends_with(",DC,LDC"));
+        assert!(lines.count() > 0);
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn saved_samples_reproduce_run() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join("serversim_pipeline_samples");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        let export_config = ExportConfig {
+            output_file: dir.join("result.csv").to_string_lossy().to_string(),
+            checkpoint: None,
+            resume: false,
+        };
+
+        let config = InstanceConfig {
+            number_of_servers: 2,
+            number_of_requests: 20,
+            min_value: 0,
+            max_value: 100,
+        };
+        let generator_config = InstanceSampleConfig {
+            number_of_instances: 6,
+            percentage_of_relocations: 0.0,
+        };
+        // Some of these samples are rejected, which shifts the positions of the
+        // remaining ones but must not change their seeds or names.
+        let sample_config =
+            SampleConfig::from_iter(&["test", "-p", "8", "-s", "20", "-m", "5", "-b", "0.25"]);
+        let simulator = Simulators::from_iter(&["test", "kserver", "--lambdas", "2"]);
+        let instances = generate_instances(&generator_config, &config, Some(7))?;
+        run(
+            instances.clone(),
+            &sample_config,
+            &simulator,
+            &export_config,
+            false,
+            Some(7),
+            None,
+        )?;
+        let direct = fs::read_to_string(&export_config.output_file)?;
+
+        let samples = sample_generator::run(instances, &sample_config, Some(7))?;
+        assert!(samples.len() < 6);
+        for file in &["samples.json", "samples.bin"] {
+            let file = dir.join(file).to_string_lossy().to_string();
+            sample_generator::save(&samples, &file)?;
+            let samples = sample_generator::load(&file)?;
+            run_samples(samples, &simulator, &export_config, false, Some(7), None)?;
+            assert_eq!(direct, fs::read_to_string(&export_config.output_file)?);
+        }
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,410 @@
+use console::style;
+use csv::ReaderBuilder;
+use plotters::prelude::*;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::{fmt, fs, io, path};
+use structopt::StructOpt;
+
+/// Columns of the exported CSV which are not costs of algorithms.
+const METADATA_COLUMNS: [&str; 6] = [
+    "NumberOfServers",
+    "NumberOfRequests",
+    "Lmbda",
+    "Eta",
+    "OptCost",
+    "Seed",
+];
+
+/// Aggregated ratios which differ by less are considered equal.
+const TOLERANCE: f64 = 1e-9;
+
+#[derive(StructOpt, Debug)]
+pub struct PlotConfig {
+    /// The CSV file exported by a simulation
+    pub file: String,
+
+    /// The lambdas plotted against eta, all by default
+    #[structopt(long, use_delimiter = true)]
+    pub lambdas: Vec<f64>,
+
+    /// Plot the maximum instead of the mean ratio of each bin
+    #[structopt(long)]
+    pub max: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlotError {
+    msg: String,
+}
+
+impl fmt::Display for PlotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl Error for PlotError {
+    fn description(&self) -> &str {
+        &self.msg
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        None
+    }
+}
+
+impl PlotError {
+    fn new(msg: String) -> PlotError {
+        PlotError { msg }
+    }
+}
+
+/// The empirical competitive ratios of all algorithm columns of an exported CSV.
+#[derive(Debug, Clone)]
+pub struct Results {
+    pub algorithms: Vec<String>,
+    rows: Vec<ResultRow>,
+}
+
+#[derive(Debug, Clone)]
+struct ResultRow {
+    lambda: f64,
+    eta_over_opt: f64,
+    ratios: Vec<f64>,
+}
+
+/// A named curve of a plot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve {
+    pub name: String,
+    pub points: Vec<(f64, f64)>,
+}
+
+/// Reads the results, where every column except the metadata and the variances is an algorithm.
+///
+/// Rows with `OptCost = 0` are skipped, since their ratio is undefined.
+pub fn parse_results<R: io::Read>(reader: R) -> Result<Results, Box<dyn Error>> {
+    let mut rdr = ReaderBuilder::new().from_reader(reader);
+    let headers = rdr.headers()?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h == name)
+            .ok_or_else(|| PlotError::new(format!("The results have no column {}", name)))
+    };
+    let (lambda, eta, opt) = (column("Lmbda")?, column("Eta")?, column("OptCost")?);
+    let algorithm_columns: Vec<usize> = (0..headers.len())
+        .filter(|&i| !METADATA_COLUMNS.contains(&&headers[i]) && !headers[i].ends_with("Var"))
+        .collect();
+
+    let mut rows = vec![];
+    for record in rdr.records() {
+        let record = record?;
+        let value = |i: usize| record[i].parse::<f64>();
+        let opt_cost = value(opt)?;
+        if opt_cost == 0.0 {
+            continue;
+        }
+        rows.push(ResultRow {
+            lambda: value(lambda)?,
+            eta_over_opt: value(eta)? / opt_cost,
+            ratios: algorithm_columns
+                .iter()
+                .map(|&i| value(i).map(|cost| cost / opt_cost))
+                .collect::<Result<Vec<f64>, _>>()?,
+        });
+    }
+
+    Ok(Results {
+        algorithms: algorithm_columns
+            .into_iter()
+            .map(|i| headers[i].to_string())
+            .collect(),
+        rows,
+    })
+}
+
+/// Groups values by an integer key and aggregates each group into its mean or maximum.
+fn aggregate(values: impl Iterator<Item = (i64, f64)>, max: bool) -> BTreeMap<i64, f64> {
+    let mut groups: BTreeMap<i64, Vec<f64>> = BTreeMap::new();
+    for (key, value) in values {
+        groups.entry(key).or_default().push(value);
+    }
+    groups
+        .into_iter()
+        .map(|(key, values)| {
+            let aggregated = if max {
+                values.iter().cloned().fold(f64::NEG_INFINITY, f64::max)
+            } else {
+                values.iter().sum::<f64>() / values.len() as f64
+            };
+            (key, aggregated)
+        })
+        .collect()
+}
+
+/// The upper end of the bin of `eta / OPT`, as in the bins of the generated predictions.
+fn bin(eta_over_opt: f64, bin_size: f64) -> i64 {
+    (eta_over_opt / bin_size).ceil() as i64
+}
+
+fn lambda_key(lambda: f64) -> i64 {
+    (lambda * 1000.0).round() as i64
+}
+
+impl Results {
+    /// The ratios of algorithm `alg` against eta for each lambda.
+    fn eta_curves(
+        &self,
+        alg: usize,
+        bin_size: f64,
+        max: bool,
+    ) -> BTreeMap<i64, BTreeMap<i64, f64>> {
+        let mut by_lambda: BTreeMap<i64, Vec<&ResultRow>> = BTreeMap::new();
+        for row in &self.rows {
+            by_lambda
+                .entry(lambda_key(row.lambda))
+                .or_default()
+                .push(row);
+        }
+        by_lambda
+            .into_iter()
+            .map(|(lambda, rows)| {
+                let points = aggregate(
+                    rows.into_iter()
+                        .map(|row| (bin(row.eta_over_opt, bin_size), row.ratios[alg])),
+                    max,
+                );
+                (lambda, points)
+            })
+            .collect()
+    }
+
+    /// Whether the ratios of algorithm `alg` differ for different lambdas.
+    fn depends_on_lambda(&self, alg: usize, bin_size: f64, max: bool) -> bool {
+        let curves = self.eta_curves(alg, bin_size, max);
+        let mut curves = curves.values();
+        match curves.next() {
+            None => false,
+            Some(first) => curves.any(|curve| {
+                curve.len() != first.len()
+                    || curve
+                        .iter()
+                        .zip(first)
+                        .any(|((b1, r1), (b2, r2))| b1 != b2 || (r1 - r2).abs() > TOLERANCE)
+            }),
+        }
+    }
+
+    /// Curves of the competitive ratio against `eta / OPT`.
+    ///
+    /// Algorithms which depend on lambda get one curve for each of the given `lambdas`, or for all if it is empty.
+    pub fn ratio_vs_eta(&self, bin_size: f64, lambdas: &[f64], max: bool) -> Vec<Curve> {
+        let selected: Vec<i64> = lambdas.iter().map(|&l| lambda_key(l)).collect();
+        let to_points = |curve: &BTreeMap<i64, f64>| {
+            curve
+                .iter()
+                .map(|(&b, &ratio)| (b as f64 * bin_size, ratio))
+                .collect()
+        };
+        let mut curves = vec![];
+        for (alg, name) in self.algorithms.iter().enumerate() {
+            let by_lambda = self.eta_curves(alg, bin_size, max);
+            if !self.depends_on_lambda(alg, bin_size, max) {
+                if let Some(curve) = by_lambda.values().next() {
+                    curves.push(Curve {
+                        name: name.clone(),
+                        points: to_points(curve),
+                    });
+                }
+                continue;
+            }
+            for (lambda, curve) in by_lambda {
+                if selected.is_empty() || selected.contains(&lambda) {
+                    curves.push(Curve {
+                        name: format!("{} (λ = {:.2})", name, lambda as f64 / 1000.0),
+                        points: to_points(&curve),
+                    });
+                }
+            }
+        }
+        curves
+    }
+
+    /// Curves of the competitive ratio against lambda.
+    ///
+    /// Algorithms which depend on lambda get one curve for each bin of `eta / OPT`.
+    pub fn ratio_vs_lambda(&self, bin_size: f64, max: bool) -> Vec<Curve> {
+        let mut curves = vec![];
+        for (alg, name) in self.algorithms.iter().enumerate() {
+            let to_points = |rows: Vec<&ResultRow>| {
+                aggregate(
+                    rows.into_iter()
+                        .map(|row| (lambda_key(row.lambda), row.ratios[alg])),
+                    max,
+                )
+                .into_iter()
+                .map(|(lambda, ratio)| (lambda as f64 / 1000.0, ratio))
+                .collect()
+            };
+            if !self.depends_on_lambda(alg, bin_size, max) {
+                curves.push(Curve {
+                    name: name.clone(),
+                    points: to_points(self.rows.iter().collect()),
+                });
+                continue;
+            }
+            let mut by_bin: BTreeMap<i64, Vec<&ResultRow>> = BTreeMap::new();
+            for row in &self.rows {
+                by_bin
+                    .entry(bin(row.eta_over_opt, bin_size))
+                    .or_default()
+                    .push(row);
+            }
+            for (b, rows) in by_bin {
+                curves.push(Curve {
+                    name: format!("{} (η/OPT ≤ {:.2})", name, b as f64 * bin_size),
+                    points: to_points(rows),
+                });
+            }
+        }
+        curves
+    }
+}
+
+/// Draws the curves together with the optimal ratio 1 as SVG.
+fn draw(curves: &[Curve], output: &path::Path, x_desc: &str) -> Result<(), Box<dyn Error>> {
+    let points = || curves.iter().flat_map(|c| c.points.iter());
+    let (x_min, x_max) = points().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+        (min.min(p.0), max.max(p.0))
+    });
+    let y_max = points().map(|p| p.1).fold(1.0, f64::max);
+    if x_min > x_max {
+        return Err(PlotError::new("There are no results to plot".to_string()).into());
+    }
+    let x_max = if x_max > x_min { x_max } else { x_min + 1.0 };
+
+    let root = SVGBackend::new(output, (1024, 768)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(x_min..x_max, 0.98..y_max * 1.02)?;
+    chart
+        .configure_mesh()
+        .x_desc(x_desc)
+        .y_desc("Empirical competitive ratio")
+        .draw()?;
+
+    chart.draw_series(LineSeries::new(vec![(x_min, 1.0), (x_max, 1.0)], &BLACK))?;
+    for (i, curve) in curves.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        chart
+            .draw_series(LineSeries::new(curve.points.clone(), color.stroke_width(2)))?
+            .label(curve.name.clone())
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        chart.draw_series(
+            curve
+                .points
+                .iter()
+                .map(|&p| Circle::new(p, 3, color.filled())),
+        )?;
+    }
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperLeft)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    root.present()?;
+
+    Ok(())
+}
+
+/// Plots the competitive ratios of an exported CSV against eta and lambda.
+///
+/// The plots are written next to the CSV as `<name>_eta.svg` and `<name>_lambda.svg`.
+pub fn run(config: &PlotConfig, bin_size: f64) -> Result<(), Box<dyn Error>> {
+    println!(
+        "{}",
+        style(format!("Start plotting {}...", config.file))
+            .bold()
+            .cyan()
+    );
+    let results = parse_results(fs::File::open(&config.file)?)?;
+    let input = path::Path::new(&config.file);
+    let output = |suffix: &str| {
+        let stem = input
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("result");
+        input.with_file_name(format!("{}_{}.svg", stem, suffix))
+    };
+
+    let eta_output = output("eta");
+    draw(
+        &results.ratio_vs_eta(bin_size, &config.lambdas, config.max),
+        &eta_output,
+        "Eta / Opt",
+    )?;
+    let lambda_output = output("lambda");
+    draw(
+        &results.ratio_vs_lambda(bin_size, config.max),
+        &lambda_output,
+        "Lambda",
+    )?;
+    println!(
+        "{} {} {}",
+        style("Plotting finished:").bold().green(),
+        eta_output.display(),
+        lambda_output.display()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "\
+NumberOfServers,NumberOfRequests,Lmbda,Eta,OptCost,DC,LDC,Harmonic,HarmonicVar
+2,3,0,0,10,20,10,30,4
+2,3,0,15,10,20,20,30,4
+2,3,1,0,10,20,15,30,4
+2,3,1,15,10,20,17,30,4
+2,3,1,5,0,20,17,30,4
+";
+
+    #[test]
+    fn parse_results_works() {
+        let results = parse_results(CSV.as_bytes()).unwrap();
+        assert_eq!(vec!["DC", "LDC", "Harmonic"], results.algorithms);
+        assert_eq!(4, results.rows.len());
+        assert_eq!(vec![2.0, 2.0, 3.0], results.rows[1].ratios);
+        assert_eq!(1.5, results.rows[1].eta_over_opt);
+    }
+
+    #[test]
+    fn curves_split_by_lambda() {
+        let results = parse_results(CSV.as_bytes()).unwrap();
+        let curves = results.ratio_vs_eta(1.0, &[], false);
+        let names: Vec<&str> = curves.iter().map(|c| c.name.as_ref()).collect();
+        assert_eq!(
+            vec!["DC", "LDC (λ = 0.00)", "LDC (λ = 1.00)", "Harmonic"],
+            names
+        );
+        assert_eq!(vec![(0.0, 1.0), (2.0, 2.0)], curves[1].points);
+
+        let curves = results.ratio_vs_eta(1.0, &[1.0], true);
+        assert_eq!(3, curves.len());
+        assert_eq!(vec![(0.0, 1.5), (2.0, 1.7)], curves[1].points);
+
+        let curves = results.ratio_vs_lambda(1.0, false);
+        assert_eq!(vec![(0.0, 2.0), (1.0, 2.0)], curves[0].points);
+        assert_eq!("LDC (η/OPT ≤ 2.00)", curves[2].name);
+        assert_eq!(vec![(0.0, 2.0), (1.0, 1.7)], curves[2].points);
+    }
+}
@@ -1,4 +1,7 @@
 use serverlib::prelude::*;
+use serverlib::Error as ServerError;
+
+use crate::sample_generator::IndexedSample;
 
 use samplelib::*;
 
@@ -25,13 +28,21 @@ pub struct PredictionConfig {
     pub preds_per_bin: usize,
 }
 
-trait PredictionAdder {
-    fn add_predictions(self, config: &PredictionConfig) -> Result<Sample, PredictionError>;
+trait PredictionAdder<M: Metric> {
+    fn add_predictions<R: Rng>(
+        self,
+        config: &PredictionConfig,
+        rng: &mut R,
+    ) -> Result<Sample<M>, ServerError>;
 }
 
-impl PredictionAdder for Sample {
-    fn add_predictions(self, config: &PredictionConfig) -> Result<Sample, PredictionError> {
-        match generate_predictions(&self.instance, &self.solution, self.opt_cost, config) {
+impl<M: Metric> PredictionAdder<M> for Sample<M> {
+    fn add_predictions<R: Rng>(
+        self,
+        config: &PredictionConfig,
+        rng: &mut R,
+    ) -> Result<Sample<M>, ServerError> {
+        match generate_predictions(&self.instance, &self.solution, self.opt_cost, config, rng) {
             Ok(preds) => Ok(Sample {
                 predictions: preds,
                 ..self
@@ -41,10 +52,25 @@ impl PredictionAdder for Sample {
     }
 }
 
-pub fn run_generate_predictions(
-    samples: Vec<Sample>,
+/// Adds predictions to the `index`-th sample, which only depend on `seed` and `index` if a seed is given.
+pub fn predict_sample<M: Metric>(
+    sample: Sample<M>,
+    config: &PredictionConfig,
+    seed: Option<u64>,
+    index: usize,
+) -> Result<Sample<M>, ServerError> {
+    sample.add_predictions(config, &mut stream_rng(seed, RngStream::Prediction, index))
+}
+
+/// Adds predictions to the samples, see `predict_sample`, and drops those without valid predictions.
+pub fn run_generate_predictions<M: Metric + Send>(
+    samples: Vec<IndexedSample<M>>,
     config: &PredictionConfig,
-) -> Result<Vec<Sample>, Box<dyn Error>> {
+    seed: Option<u64>,
+) -> Result<Vec<IndexedSample<M>>, Box<dyn Error>>
+where
+    M::Point: Send,
+{
     let pb = ProgressBar::new(samples.len() as u64);
 
     pb.set_style(
@@ -55,42 +81,43 @@ pub fn run_generate_predictions(
     let samples_with_preds = samples
         .into_par_iter()
         .progress_with(pb)
-        .map(|sample| sample.add_predictions(config))
-        .filter_map(Result::ok)
+        .filter_map(|(index, name, sample)| {
+            let sample = predict_sample(sample, config, seed, index).ok()?;
+            Some((index, name, sample))
+        })
         .collect();
 
     Ok(samples_with_preds)
 }
 
 /// Lower and upper inclusive
-fn predict(lower: usize, upper: usize) -> usize {
+fn predict<R: Rng>(lower: usize, upper: usize, rng: &mut R) -> usize {
     if lower == upper {
         return lower;
     }
-    let mut rng = rand::thread_rng();
     rng.gen_range(lower..upper + 1)
 }
 
-pub fn generate_predictions(
-    instance: &Instance,
-    solution: &Schedule,
-    opt_cost: u32,
+pub fn generate_predictions<M: Metric, R: Rng>(
+    instance: &Instance<M>,
+    solution: &Schedule<M::Point>,
+    opt_cost: f64,
     config: &PredictionConfig,
-) -> Result<Vec<Prediction>, PredictionError> {
+    rng: &mut R,
+) -> Result<Vec<Prediction>, ServerError> {
     let mut step_to_predictions: Vec<Vec<(Prediction, f32)>> =
-        vec![vec![]; config.number_of_predictions as usize];
+        vec![vec![]; config.number_of_predictions];
 
-    let perfect_prediction = solution.to_prediction(instance);
-    let ref_perfect_prediction = &solution.to_prediction(instance);
-    step_to_predictions[0].push((perfect_prediction, 0.0));
+    let perfect_prediction = solution.try_to_prediction(instance)?;
+    step_to_predictions[0].push((perfect_prediction.clone(), 0.0));
+    let ref_perfect_prediction = &perfect_prediction;
 
-    let mut rng = rand::thread_rng();
     let dist = Uniform::from(0..instance.length());
 
     for number_of_wrong_servers in 1..instance.length() {
         for _ in 1..config.number_of_samples_per_round {
             let mut correct_preds = vec![true; instance.length()];
-            (1..number_of_wrong_servers).for_each(|_| correct_preds[dist.sample(&mut rng)] = false);
+            (1..number_of_wrong_servers).for_each(|_| correct_preds[dist.sample(rng)] = false);
 
             let mut pred_vec = vec![];
             for (i, &server) in ref_perfect_prediction.into_iter().enumerate() {
@@ -98,7 +125,7 @@ pub fn generate_predictions(
                 if correct_preds[i] {
                     pred_vec.push(server);
                 } else {
-                    let p = predict(0, k - 1);
+                    let p = predict(0, k - 1, rng);
                     if p == server {
                         if p == 0 {
                             pred_vec.push(p + 1);
@@ -112,19 +139,20 @@ pub fn generate_predictions(
             }
 
             let pred = Prediction::from(pred_vec);
-            let pred_schedule = pred.to_schedule(instance);
+            let pred_schedule = pred.try_to_schedule(instance)?;
             // println!(
             //     "Instance length {}, Solution length {}, Pred length {}",
             //     instance.length(),
             //     solution.size(),
             //     pred.0.len()
             // );
-            let eta = pred_schedule.cost() - solution.cost();
-            let ratio = eta as f32 / opt_cost as f32;
+            let eta = pred_schedule.metric_cost(instance.metric())
+                - solution.metric_cost(instance.metric());
+            let ratio = (eta / opt_cost) as f32;
             let bin_index: usize = (ratio / config.step_size).ceil() as usize;
 
             if bin_index < config.number_of_predictions {
-                step_to_predictions[bin_index].push((pred, ratio as f32));
+                step_to_predictions[bin_index].push((pred, ratio));
             }
 
             if !step_to_predictions
@@ -161,22 +189,24 @@ pub fn generate_predictions(
             })
             .collect();
 
-        return Err(PredictionError::new(format!("{} missing!", msgs.join(","))));
+        Err(ServerError::MissingPredictions(format!(
+            "{} missing!",
+            msgs.join(",")
+        )))
     } else {
         Ok(step_to_predictions
             .into_iter()
-            .map(|mut preds| {
+            .flat_map(|mut preds| {
                 preds.sort_by(|(_, err1), (_, err2)| err1.partial_cmp(err2).unwrap());
                 let largest = preds.last().unwrap().0.clone();
                 preds.swap_remove(preds.len() - 1);
                 let mut others = preds
-                    .choose_multiple(&mut rng, config.preds_per_bin - 1)
+                    .choose_multiple(rng, config.preds_per_bin - 1)
                     .map(|(pred, _)| pred.clone())
                     .collect::<Vec<Prediction>>();
                 others.push(largest);
                 others
             })
-            .flatten()
             .collect())
     }
 }
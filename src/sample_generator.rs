@@ -1,9 +1,11 @@
-use serverlib::prelude::Instance;
+use serverlib::prelude::{CostModel, Instance, Line, Metric};
 
 use crate::pred_generator::{run_generate_predictions, PredictionConfig};
 use samplelib::*;
 
-use crate::solver::SampleBuilder;
+use crate::instance_generator::NamedInstance;
+use crate::opt_cache::OptCache;
+use crate::solver::{SampleBuilder, SolverType};
 use console::style;
 use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
 use rayon::prelude::*;
@@ -14,15 +16,49 @@ use structopt::StructOpt;
 pub struct SampleConfig {
     #[structopt(flatten)]
     pub pred_config: PredictionConfig,
+
+    /// Offline solver used to compute OPT: "mcmf" or "dp" (exact, only for small k)
+    #[structopt(long = "solver", default_value = "mcmf")]
+    pub solver: SolverType,
+
+    /// Cost model of the k-taxi problem: "easy" or "hard" (relocations are charged)
+    #[structopt(long = "cost_model", default_value = "easy")]
+    pub cost_model: CostModel,
+
+    /// Directory in which optimal solutions are cached across runs
+    #[structopt(long = "opt-cache")]
+    pub opt_cache: Option<String>,
 }
 
-pub fn run(instances: Vec<Instance>, config: &SampleConfig) -> Result<Vec<Sample>, Box<dyn Error>> {
+impl SampleConfig {
+    /// Opens the OPT cache if a directory is given.
+    pub fn open_cache(&self) -> Result<Option<OptCache>, std::io::Error> {
+        self.opt_cache.as_ref().map(OptCache::new).transpose()
+    }
+}
+
+/// A sample together with the index and the name of its instance, which determine its random streams and
+/// identify it in checkpoints, such that a saved sample is simulated like its instance in a direct run.
+pub type IndexedSample<M = Line> = (usize, String, Sample<M>);
+
+/// Solves the instances and generates their predictions, where the predictions of the `i`-th instance only
+/// depend on `seed` and `i` if a seed is given.
+pub fn run<M: Metric + Send>(
+    instances: Vec<NamedInstance<M>>,
+    config: &SampleConfig,
+    seed: Option<u64>,
+) -> Result<Vec<IndexedSample<M>>, Box<dyn Error>>
+where
+    M::Point: Send,
+    Instance<M>: SampleBuilder<M>,
+{
     println!("{}", style("Start generating samples...").bold().cyan());
     println!("{} Solving instances...", style("[1/2]").bold().dim());
-    let samples = solve_instances(instances)?;
+    let cache = config.open_cache()?;
+    let samples = solve_instances(instances, config.solver, config.cost_model, cache.as_ref())?;
     let number_of_samples = samples.len();
     println!("{} Generating predictions...", style("[2/2]").bold().dim());
-    let samples_with_preds = run_generate_predictions(samples, &config.pred_config)?;
+    let samples_with_preds = run_generate_predictions(samples, &config.pred_config, seed)?;
     let number_of_rejected_samples = number_of_samples - samples_with_preds.len();
     if number_of_rejected_samples > 0 {
         println!(
@@ -39,18 +75,53 @@ pub fn run(instances: Vec<Instance>, config: &SampleConfig) -> Result<Vec<Sample
     Ok(samples_with_preds)
 }
 
-fn solve_instances(instances: Vec<Instance>) -> Result<Vec<Sample>, Box<dyn Error>> {
+/// Writes samples to `file`, as JSON if it ends with `.json` and in a binary format otherwise.
+pub fn save(samples: &[IndexedSample], file: &str) -> Result<(), Box<dyn Error>> {
+    println!(
+        "{}",
+        style(format!("Saving {} samples to {}...", samples.len(), file))
+            .bold()
+            .cyan()
+    );
+    samplelib::save(&samples, file, Format::from_path(file))
+}
+
+/// Reads the samples written by `save`.
+pub fn load(file: &str) -> Result<Vec<IndexedSample>, Box<dyn Error>> {
+    let samples: Vec<IndexedSample> = samplelib::load(file, Format::from_path(file))?;
+    println!(
+        "{} samples loaded from {}.",
+        style(samples.len()).bold().green(),
+        file
+    );
+    Ok(samples)
+}
+
+fn solve_instances<M: Metric + Send>(
+    instances: Vec<NamedInstance<M>>,
+    solver: SolverType,
+    model: CostModel,
+    cache: Option<&OptCache>,
+) -> Result<Vec<IndexedSample<M>>, Box<dyn Error>>
+where
+    M::Point: Send,
+    Instance<M>: SampleBuilder<M>,
+{
     let pb = ProgressBar::new(instances.len() as u64);
     pb.set_style(
         ProgressStyle::default_bar().template(
             "{msg} {spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] ({pos}/{len})",
         ),
     );
+    // unsolved instances are dropped, hence the index is taken before
     let solutions = instances
         .into_par_iter()
+        .enumerate()
         .progress_with(pb)
-        .map(|instance| instance.build_sample())
-        .filter_map(Result::ok)
+        .filter_map(|(index, (name, instance))| {
+            let sample = instance.build_sample(solver, model, cache).ok()?;
+            Some((index, name, sample))
+        })
         .collect();
 
     Ok(solutions)
@@ -1,27 +1,84 @@
-use console::style;
-use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
 use itertools_num::linspace;
-use kserver::simulate_kserver;
+use kserver::algorithms::{WorkFunction, ALGORITHM_NAMES};
+use kserver::{
+    simulate_kserver, simulate_kserver_circle, simulate_kserver_plane, simulate_kserver_tree,
+};
 use ktaxi::simulate_ktaxi;
-use rayon::prelude::*;
+use rand::rngs::StdRng;
 use samplelib::*;
+use serverlib::prelude::{Circle, Line, Metric, Plane, Tree};
 use std::error::Error;
 use std::fmt;
 use structopt::StructOpt;
 
-#[derive(StructOpt, Debug, Copy, Clone)]
+#[derive(StructOpt, Debug, Clone)]
 pub struct SimConfig {
     #[structopt(long = "lambdas", default_value = "5")]
     pub number_of_lambdas: usize,
 
-    #[structopt(short = "s", long = "gamma", default_value = "1.0")]
+    /// Simulate exactly these lambdas instead of `--lambdas` evenly spaced ones in [0,1]
+    #[structopt(long = "lambda_values", use_delimiter = true)]
+    pub lambda_values: Vec<f32>,
+
+    /// Growth of the bounds of the deterministic combinations, which must be positive
+    #[structopt(short = "s", long = "gamma", default_value = "1.0", parse(try_from_str = parse_gamma))]
     pub gamma: f64,
 
     #[structopt(short, long)]
     pub lazy: bool,
+
+    /// Additionally simulate the Work Function Algorithm, which is rejected unless k and the length are small
+    #[structopt(long)]
+    pub wfa: bool,
+
+    /// Simulate the k-taxi algorithms for any number of servers instead of BiasedDC, which requires k = 2
+    #[structopt(long)]
+    pub general: bool,
+
+    /// Number of runs of the randomized algorithms, which are only simulated if positive
+    #[structopt(long, default_value = "0")]
+    pub runs: usize,
+
+    /// Learning rate of the randomized combinations, which must be in (0, 1)
+    #[structopt(long, default_value = "0.5", parse(try_from_str = parse_beta))]
+    pub beta: f64,
+
+    /// Additionally simulate a combination of the given algorithms, e.g. "ftp,dc,ldc"
+    #[structopt(long, use_delimiter = true, parse(try_from_str = parse_algorithm_name))]
+    pub combine: Vec<String>,
 }
 
-#[derive(StructOpt, Debug, Copy, Clone)]
+pub(crate) fn parse_algorithm_name(name: &str) -> Result<String, String> {
+    if ALGORITHM_NAMES.contains(&name) {
+        Ok(name.to_string())
+    } else {
+        Err(format!(
+            "unknown algorithm {}, expected one of {}",
+            name,
+            ALGORITHM_NAMES.join(", ")
+        ))
+    }
+}
+
+fn parse_gamma(gamma: &str) -> Result<f64, String> {
+    let gamma: f64 = gamma.parse().map_err(|e| format!("{}", e))?;
+    if gamma > 0.0 && gamma.is_finite() {
+        Ok(gamma)
+    } else {
+        Err(format!("gamma {} is not a positive number", gamma))
+    }
+}
+
+fn parse_beta(beta: &str) -> Result<f64, String> {
+    let beta: f64 = beta.parse().map_err(|e| format!("{}", e))?;
+    if beta > 0.0 && beta < 1.0 {
+        Ok(beta)
+    } else {
+        Err(format!("the learning rate {} is not in (0, 1)", beta))
+    }
+}
+
+#[derive(StructOpt, Debug, Clone)]
 pub enum Simulators {
     #[structopt(name = "kserver")]
     KServer(SimConfig),
@@ -34,16 +91,15 @@ pub struct SimulatorError {
     msg: String,
 }
 
-#[allow(dead_code)]
 impl SimulatorError {
-    fn new(msg: String) -> SimulatorError {
-        SimulatorError { msg: msg }
+    pub(crate) fn new(msg: String) -> SimulatorError {
+        SimulatorError { msg }
     }
 }
 
 impl fmt::Display for SimulatorError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Invalid sample")
+        write!(f, "Invalid sample: {}", self.msg)
     }
 }
 
@@ -57,51 +113,162 @@ impl Error for SimulatorError {
     }
 }
 
-trait Simulate {
+/// Simulates the algorithms of a simulator that support the metric space `M`.
+pub trait Simulate<M: Metric = Line> {
     fn simulate(
         &self,
-        simulator: Simulators,
+        simulator: &Simulators,
         gamma: f64,
         lambda: f32,
         lazy: bool,
-    ) -> Vec<SimResult>;
+        debug: bool,
+        rng: &mut StdRng,
+    ) -> Result<Vec<SimResult>, SimulatorError>;
 }
 
 impl Simulate for Sample {
     fn simulate(
         &self,
-        simulator: Simulators,
+        simulator: &Simulators,
         gamma: f64,
         lambda: f32,
         lazy: bool,
-    ) -> Vec<SimResult> {
+        debug: bool,
+        rng: &mut StdRng,
+    ) -> Result<Vec<SimResult>, SimulatorError> {
+        match simulator {
+            Simulators::KServer(config) => simulate_kserver(
+                self,
+                gamma,
+                lambda,
+                lazy,
+                config.wfa,
+                config.runs,
+                config.beta,
+                &config.combine,
+                debug,
+                rng,
+            )
+            .map_err(|e| SimulatorError::new(e.to_string())),
+            Simulators::KTaxi(config) => {
+                check_instances(simulator, self.instance.k(), self.instance.length())?;
+                simulate_ktaxi(self, lambda, config.general, debug)
+                    .map_err(|e| SimulatorError::new(e.to_string()))
+            }
+        }
+    }
+}
+
+impl Simulate<Circle> for Sample<Circle> {
+    fn simulate(
+        &self,
+        simulator: &Simulators,
+        _: f64,
+        lambda: f32,
+        _: bool,
+        _: bool,
+        _: &mut StdRng,
+    ) -> Result<Vec<SimResult>, SimulatorError> {
         match simulator {
-            Simulators::KServer(_) => simulate_kserver(self, gamma, lambda, lazy),
-            Simulators::KTaxi(_) => simulate_ktaxi(self, lambda),
+            Simulators::KServer(_) => Ok(simulate_kserver_circle(self, lambda)),
+            Simulators::KTaxi(_) => Err(SimulatorError::new(
+                "k-taxi is not supported on the circle".to_string(),
+            )),
         }
     }
 }
 
-pub fn run(samples: Vec<Sample>, simulator: Simulators) -> Vec<SimResult> {
-    println!("{}", style("Start simulating...").bold().cyan());
-    let number_of_lambdas = match simulator {
-        Simulators::KTaxi(config) => config.number_of_lambdas,
-        Simulators::KServer(config) => config.number_of_lambdas,
+impl Simulate<Plane> for Sample<Plane> {
+    fn simulate(
+        &self,
+        simulator: &Simulators,
+        _: f64,
+        lambda: f32,
+        _: bool,
+        _: bool,
+        _: &mut StdRng,
+    ) -> Result<Vec<SimResult>, SimulatorError> {
+        match simulator {
+            Simulators::KServer(_) => Ok(simulate_kserver_plane(self, lambda)),
+            Simulators::KTaxi(_) => Err(SimulatorError::new(
+                "k-taxi is not supported in the plane".to_string(),
+            )),
+        }
+    }
+}
+
+impl Simulate<Tree> for Sample<Tree> {
+    fn simulate(
+        &self,
+        simulator: &Simulators,
+        _: f64,
+        lambda: f32,
+        _: bool,
+        _: bool,
+        _: &mut StdRng,
+    ) -> Result<Vec<SimResult>, SimulatorError> {
+        match simulator {
+            Simulators::KServer(_) => Ok(simulate_kserver_tree(self, lambda)),
+            Simulators::KTaxi(_) => Err(SimulatorError::new(
+                "k-taxi is not supported on trees".to_string(),
+            )),
+        }
+    }
+}
+
+/// Rejects instances with `k` servers and `length` requests which are not supported by `simulator`.
+///
+/// BiasedDC, which is simulated by k-taxi unless `--general` is given, only supports two servers, and the
+/// Work Function Algorithm is only tractable for small `k` and short instances.
+pub fn check_instances(
+    simulator: &Simulators,
+    k: usize,
+    length: usize,
+) -> Result<(), SimulatorError> {
+    match simulator {
+        Simulators::KTaxi(config) if !config.general && k != 2 => {
+            Err(SimulatorError::new(format!(
+                "BiasedDC requires exactly 2 servers, but k = {}, use --general for other k",
+                k
+            )))
+        }
+        Simulators::KServer(config)
+            if (config.wfa || config.combine.iter().any(|name| name == "wfa"))
+                && !WorkFunction::is_tractable(k, length) =>
+        {
+            Err(SimulatorError::new(format!(
+                "the Work Function Algorithm is intractable for k = {} and {} requests",
+                k, length
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The simulated lambdas, which are either given explicitly or evenly spaced in [0,1].
+pub fn lambdas(simulator: &Simulators) -> Vec<f32> {
+    let config = match simulator {
+        Simulators::KTaxi(config) => config,
+        Simulators::KServer(config) => config,
     };
-    let lambdas = linspace::<f32>(0., 1., number_of_lambdas)
-        .into_iter()
-        .collect::<Vec<f32>>();
-    let results = simulate_samples(samples, lambdas, simulator);
-    println!("{}", style("Simulation finished!").bold().green());
-    println!(
-        "{} {}",
-        style("Number of results: ").bold().green(),
-        style(results.len()).bold().red()
-    );
-    results
-}
-
-fn simulate_sample(sample: Sample, lambdas: &Vec<f32>, simulator: Simulators) -> Vec<SimResult> {
+    if config.lambda_values.is_empty() {
+        linspace::<f32>(0., 1., config.number_of_lambdas).collect::<Vec<f32>>()
+    } else {
+        config.lambda_values.clone()
+    }
+}
+
+/// Simulates a sample for all lambdas, where the randomized algorithms draw their choices from `rng`.
+pub fn simulate_sample<M: Metric>(
+    sample: Sample<M>,
+    lambdas: &[f32],
+    simulator: &Simulators,
+    debug: bool,
+    rng: &mut StdRng,
+) -> Result<Vec<SimResult>, SimulatorError>
+where
+    Sample<M>: Simulate<M>,
+{
     let gamma = match simulator {
         Simulators::KTaxi(config) => config.gamma,
         Simulators::KServer(config) => config.gamma,
@@ -112,40 +279,8 @@ fn simulate_sample(sample: Sample, lambdas: &Vec<f32>, simulator: Simulators) ->
     };
     let results = lambdas
         .iter()
-        .map(|lambda| sample.simulate(simulator, gamma, *lambda, lazy))
-        .flatten()
-        .collect::<Vec<SimResult>>();
-
-    return results;
-}
-
-fn simulate_samples(
-    samples: Vec<Sample>,
-    lambdas: Vec<f32>,
-    simulator: Simulators,
-) -> Vec<SimResult> {
-    let number_of_samples = samples.len();
-    let pb = ProgressBar::new(number_of_samples as u64);
-
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] ({pos}/{len})"),
-    );
-
-    let results: Vec<Vec<SimResult>> = samples
-        .into_par_iter()
-        .progress_with(pb)
-        .map(|sample| simulate_sample(sample, &lambdas, simulator))
-        .collect::<Vec<Vec<SimResult>>>();
-
-    let failed_simulations = number_of_samples - results.len();
-    if failed_simulations > 0 {
-        println!(
-            "{} {}",
-            style("Samples with invalid simulation: ").bold().green(),
-            style(failed_simulations).bold().red()
-        );
-    }
-    let res = results.into_iter().flatten().collect::<Vec<SimResult>>();
-    res
+        .map(|lambda| sample.simulate(simulator, gamma, *lambda, lazy, debug, rng))
+        .collect::<Result<Vec<Vec<SimResult>>, SimulatorError>>()?;
+
+    Ok(results.into_iter().flatten().collect())
 }
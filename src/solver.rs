@@ -1,18 +1,30 @@
 use serverlib::prelude::*;
 
+use crate::dp_solver::DPSolver;
+use crate::opt_cache::{solve_cached, CacheMetric, OptCache};
+use crate::taxi_solver::TaxiSolver;
 use samplelib::*;
-use mcmf::*;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
+use std::ops::{Add, Neg, Sub};
+use std::str::FromStr;
 
-#[derive(Copy, Clone, Ord, PartialOrd, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, PartialEq, Eq)]
 enum VertexType {
     InitVertex(usize),
     FromVertex(usize),
     ToVertex(usize),
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Vertex {
+    Source,
+    Sink,
+    Node(VertexType),
+}
+
 #[derive(Debug, Clone)]
 pub struct SolverError {
     msg: String,
@@ -20,17 +32,13 @@ pub struct SolverError {
 
 impl fmt::Display for SolverError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "Not all required predictions have been found: {}!",
-            self.msg
-        )
+        write!(f, "Cannot solve instance: {}!", self.msg)
     }
 }
 
 impl Error for SolverError {
     fn description(&self) -> &str {
-        "Not all required predictions have been found!"
+        "Cannot solve instance!"
     }
 
     fn cause(&self) -> Option<&dyn Error> {
@@ -40,125 +48,484 @@ impl Error for SolverError {
 
 #[allow(dead_code)]
 impl SolverError {
-    fn new(msg: String) -> SolverError {
-        SolverError { msg: msg }
+    pub(crate) fn new(msg: String) -> SolverError {
+        SolverError { msg }
+    }
+}
+
+/// The offline algorithm used to compute an optimal solution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SolverType {
+    /// Min-cost-flow formulation solved by successive shortest paths.
+    MinCostFlow,
+    /// Exact dynamic program over configurations, only tractable for small k.
+    DynamicProgram,
+}
+
+impl FromStr for SolverType {
+    type Err = SolverError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mcmf" => Ok(SolverType::MinCostFlow),
+            "dp" => Ok(SolverType::DynamicProgram),
+            _ => Err(SolverError::new(format!("Unknown solver {}", s))),
+        }
     }
 }
 
-const COST_CONST: i32 = -100000;
+/// Computes an optimal solution of an instance, where `P` is the point type of its metric space.
+pub trait Solver<P = f32> {
+    fn solve(&self) -> Result<(Schedule<P>, f64), SolverError>;
 
-pub trait Solver {
-    fn solve(&self) -> Result<(Schedule, u32), SolverError>;
+    /// Computes an optimal solution, where relocations are charged according to `model`.
+    fn solve_with_model(&self, model: CostModel) -> Result<(Schedule<P>, f64), SolverError>;
 }
 
 impl Solver for Instance {
-    fn solve(&self) -> Result<(Schedule, u32), SolverError> {
-        let mut graph = GraphBuilder::new();
-        add_source_and_init_vertices(&mut graph, self);
-        add_request_verticies(&mut graph, self);
-        add_request_edges(&mut graph, self);
-        let (costs, paths) = graph.mcmf();
-        let schedule = create_schedule(paths, self);
+    fn solve(&self) -> Result<(Schedule, f64), SolverError> {
+        let (tuples, costs) = min_cost_flow(self)?;
+        let schedule = create_schedule(tuples, self);
+        Ok((schedule, costs))
+    }
 
-        let fixed_costs = costs as i32 + (-COST_CONST * self.length() as i32);
-        return Ok((schedule, fixed_costs as u32));
+    fn solve_with_model(&self, model: CostModel) -> Result<(Schedule, f64), SolverError> {
+        // the flow network charges the easy cost, relocations add the same constant to every schedule
+        let (schedule, costs) = self.solve()?;
+        Ok((schedule, costs + model.relocation_costs(self)))
     }
+}
+
+/// Computes an optimal solution of an instance in any metric space.
+///
+/// The min-cost-flow network only depends on the distances between requests and servers. In contrast
+/// to `Solver`, which exchanges servers on the line to keep the schedule sorted, servers keep their
+/// index.
+pub trait MetricSolver<M: Metric> {
+    fn solve_metric(&self) -> Result<(Schedule<M::Point>, f64), SolverError>;
+}
+
+impl<M: Metric> MetricSolver<M> for Instance<M> {
+    fn solve_metric(&self) -> Result<(Schedule<M::Point>, f64), SolverError> {
+        let (tuples, costs) = min_cost_flow(self)?;
+        let schedule = create_metric_schedule(tuples, self);
+        Ok((schedule, costs))
+    }
+}
 
-    
+/// Solves the flow network of an instance and returns the served `(server, request)` tuples.
+fn min_cost_flow<M: Metric>(
+    instance: &Instance<M>,
+) -> Result<(Vec<(usize, usize)>, f64), SolverError> {
+    if instance.k() == 0 {
+        return Err(SolverError::new("Instance has no servers".to_string()));
+    }
+    let mut network = FlowNetwork::new(instance);
+    for _ in 0..instance.k() {
+        if !network.augment() {
+            break;
+        }
+    }
+    if network.uncovered_requests() > 0 {
+        return Err(SolverError::new(format!(
+            "{} requests cannot be served",
+            network.uncovered_requests()
+        )));
+    }
+    Ok(network.paths())
 }
 
-pub trait SampleBuilder {
-    fn build_sample(self) -> Result<Sample, SolverError>;
+/// Builds the sample of an instance by computing an optimal solution, which is looked up in and added to
+/// the OPT cache if one is given.
+pub trait SampleBuilder<M: Metric = Line> {
+    fn build_sample(
+        self,
+        solver: SolverType,
+        model: CostModel,
+        cache: Option<&OptCache>,
+    ) -> Result<Sample<M>, SolverError>;
 }
 
 impl SampleBuilder for Instance {
-    fn build_sample(self) -> Result<Sample, SolverError> {
-        let (solution, costs) = self.solve()?;
-        Ok(Sample::new(self, solution, costs))
+    fn build_sample(
+        self,
+        solver: SolverType,
+        model: CostModel,
+        cache: Option<&OptCache>,
+    ) -> Result<Sample, SolverError> {
+        let is_taxi = self.requests().iter().any(|req| !req.is_simple());
+        let (solution, costs) = solve_cached(cache, &self, solver, model, || match solver {
+            SolverType::MinCostFlow if is_taxi => self.solve_taxi(model),
+            SolverType::MinCostFlow => self.solve_with_model(model),
+            SolverType::DynamicProgram => {
+                let (solution, costs) = self.solve_dp()?;
+                Ok((solution, costs + model.relocation_costs(&self)))
+            }
+        })?;
+        Ok(Sample {
+            cost_model: model,
+            ..Sample::new(self, solution, costs)
+        })
     }
 }
 
-fn add_source_and_init_vertices(graph: &mut GraphBuilder<VertexType>, instance: &Instance) {
-    for (i, _) in instance.initial_positions().into_iter().enumerate() {
-        graph.add_edge(
-            Vertex::Source,
-            VertexType::InitVertex(i),
-            Capacity(1),
-            Cost(0),
-        );
-        graph.add_edge(
-            VertexType::InitVertex(i),
-            Vertex::Sink,
-            Capacity(1),
-            Cost(0),
-        );
+impl SampleBuilder<Circle> for Instance<Circle> {
+    fn build_sample(
+        self,
+        solver: SolverType,
+        model: CostModel,
+        cache: Option<&OptCache>,
+    ) -> Result<Sample<Circle>, SolverError> {
+        build_metric_sample(self, solver, model, cache)
     }
 }
 
-fn add_request_verticies(graph: &mut GraphBuilder<VertexType>, instance: &Instance) {
-    for (i, x) in instance.requests().iter().enumerate() {
-        for (j, y) in instance.initial_positions().into_iter().enumerate() {
-            graph.add_edge(
-                VertexType::InitVertex(j),
-                VertexType::FromVertex(i),
-                Capacity(1),
-                Cost(x.distance_from(y) as i32),
-            );
+impl SampleBuilder<Plane> for Instance<Plane> {
+    fn build_sample(
+        self,
+        solver: SolverType,
+        model: CostModel,
+        cache: Option<&OptCache>,
+    ) -> Result<Sample<Plane>, SolverError> {
+        build_metric_sample(self, solver, model, cache)
+    }
+}
+
+impl SampleBuilder<Tree> for Instance<Tree> {
+    fn build_sample(
+        self,
+        solver: SolverType,
+        model: CostModel,
+        cache: Option<&OptCache>,
+    ) -> Result<Sample<Tree>, SolverError> {
+        build_metric_sample(self, solver, model, cache)
+    }
+}
+
+fn build_metric_sample<M: CacheMetric>(
+    instance: Instance<M>,
+    solver: SolverType,
+    model: CostModel,
+    cache: Option<&OptCache>,
+) -> Result<Sample<M>, SolverError> {
+    if solver != SolverType::MinCostFlow {
+        return Err(SolverError::new(
+            "The dynamic program only supports the line".to_string(),
+        ));
+    }
+    let (solution, costs) = solve_cached(cache, &instance, solver, model, || {
+        let (solution, costs) = instance.solve_metric()?;
+        Ok((solution, costs + model.relocation_costs(&instance)))
+    })?;
+    Ok(Sample {
+        cost_model: model,
+        ..Sample::new(instance, solution, costs)
+    })
+}
+
+/// Cost of an edge or path in the flow network.
+///
+/// Instead of a large negative constant on the request edges, costs are ordered
+/// lexicographically: covering a request always dominates the travelled distance.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+struct Cost {
+    uncovered: i64,
+    distance: f64,
+}
+
+impl Cost {
+    fn distance(distance: f64) -> Cost {
+        Cost {
+            uncovered: 0,
+            distance,
+        }
+    }
+
+    fn cover() -> Cost {
+        Cost {
+            uncovered: -1,
+            distance: 0.0,
         }
-        graph.add_edge(
-            VertexType::FromVertex(i),
-            VertexType::ToVertex(i),
-            Capacity(1),
-            Cost(COST_CONST),
-        );
-        graph.add_edge(VertexType::ToVertex(i), Vertex::Sink, Capacity(1), Cost(0));
     }
 }
 
-fn add_request_edges(graph: &mut GraphBuilder<VertexType>, instance: &Instance) {
-    for (i, x) in instance.requests().iter().enumerate() {
-        for (j, y) in instance.requests().iter().enumerate() {
-            if i < j {
-                graph.add_edge(
-                    VertexType::ToVertex(i),
-                    VertexType::FromVertex(j),
-                    Capacity(1),
-                    Cost(x.distance_to_req(y) as i32),
-                );
-            }
+impl Add for Cost {
+    type Output = Cost;
+    fn add(self, other: Cost) -> Cost {
+        Cost {
+            uncovered: self.uncovered + other.uncovered,
+            distance: self.distance + other.distance,
         }
     }
 }
 
-fn get_type_of_vertex(v: &Vertex<VertexType>) -> Option<VertexType> {
-    match v {
-        Vertex::Node(x) => Some::<VertexType>(*x),
-        _ => None,
+impl Sub for Cost {
+    type Output = Cost;
+    fn sub(self, other: Cost) -> Cost {
+        self + (-other)
     }
 }
 
-fn is_move_edge(v1: &Vertex<VertexType>, v2: &Vertex<VertexType>) -> Option<usize> {
-    let (type1, type2) = (get_type_of_vertex(v1), get_type_of_vertex(v2));
-    match (type1, type2) {
-        (Some(VertexType::FromVertex(i)), Some(VertexType::ToVertex(_))) => Some(i),
-        _ => None,
+impl Neg for Cost {
+    type Output = Cost;
+    fn neg(self) -> Cost {
+        Cost {
+            uncovered: -self.uncovered,
+            distance: -self.distance,
+        }
     }
 }
 
-fn create_schedule(paths: Vec<mcmf::Path<VertexType>>, instance: &Instance) -> Schedule {
+/// The min-cost-flow network of an instance with unit capacities.
+///
+/// The source is connected to every `InitVertex`, which are connected to the sink and to every
+/// `FromVertex`. Each request is an edge `FromVertex(i) -> ToVertex(i)`, and `ToVertex(i)` is
+/// connected to the sink and to every `FromVertex(j)` with `i < j`. Edges are never stored, but
+/// enumerated on demand, hence memory is linear in the number of requests. Since every vertex
+/// except source and sink has a throughput of at most one, the flow is stored as successor and
+/// predecessor of each vertex.
+struct FlowNetwork<'a, M: Metric> {
+    instance: &'a Instance<M>,
+    potentials: Vec<Cost>,
+    next: Vec<Option<usize>>,
+    prev: Vec<Option<usize>>,
+}
+
+const SOURCE: usize = 0;
+const SINK: usize = 1;
+
+impl<'a, M: Metric> FlowNetwork<'a, M> {
+    fn new(instance: &'a Instance<M>) -> FlowNetwork<'a, M> {
+        let number_of_vertices = 2 + instance.k() + 2 * instance.length();
+        let mut network = FlowNetwork {
+            instance,
+            potentials: vec![Cost::default(); number_of_vertices],
+            next: vec![None; number_of_vertices],
+            prev: vec![None; number_of_vertices],
+        };
+        network.init_potentials();
+        network
+    }
+
+    fn index(&self, vertex: Vertex) -> usize {
+        match vertex {
+            Vertex::Source => SOURCE,
+            Vertex::Sink => SINK,
+            Vertex::Node(VertexType::InitVertex(j)) => 2 + j,
+            Vertex::Node(VertexType::FromVertex(i)) => 2 + self.instance.k() + 2 * i,
+            Vertex::Node(VertexType::ToVertex(i)) => 3 + self.instance.k() + 2 * i,
+        }
+    }
+
+    fn vertex(&self, index: usize) -> Vertex {
+        let k = self.instance.k();
+        match index {
+            SOURCE => Vertex::Source,
+            SINK => Vertex::Sink,
+            i if i < 2 + k => Vertex::Node(VertexType::InitVertex(i - 2)),
+            i if (i - 2 - k).is_multiple_of(2) => {
+                Vertex::Node(VertexType::FromVertex((i - 2 - k) / 2))
+            }
+            i => Vertex::Node(VertexType::ToVertex((i - 2 - k) / 2)),
+        }
+    }
+
+    /// Shortest path distances in the network without flow, which is acyclic.
+    fn init_potentials(&mut self) {
+        let instance = self.instance;
+        let metric = instance.metric();
+        let mut best_to = Cost::default();
+        for (i, req) in instance.requests().iter().enumerate() {
+            let mut from = Cost::distance(
+                instance
+                    .initial_positions()
+                    .into_iter()
+                    .map(|x| metric.distance(req.pos(), x) as f64)
+                    .fold(f64::INFINITY, f64::min),
+            );
+            for (h, prev) in instance.requests().iter().take(i).enumerate() {
+                let to = self.potentials[self.index(Vertex::Node(VertexType::ToVertex(h)))];
+                let c = to + Cost::distance(metric.distance(req.pos(), prev.end()) as f64);
+                if c < from {
+                    from = c;
+                }
+            }
+            let from_idx = self.index(Vertex::Node(VertexType::FromVertex(i)));
+            let to_idx = self.index(Vertex::Node(VertexType::ToVertex(i)));
+            self.potentials[from_idx] = from;
+            self.potentials[to_idx] = from + Cost::cover();
+            if self.potentials[to_idx] < best_to {
+                best_to = self.potentials[to_idx];
+            }
+        }
+        self.potentials[SINK] = best_to;
+    }
+
+    /// The cost of the edge `u -> v` of the network.
+    fn edge_cost(&self, u: usize, v: usize) -> Cost {
+        let requests = self.instance.requests();
+        let metric = self.instance.metric();
+        match (self.vertex(u), self.vertex(v)) {
+            (Vertex::Node(VertexType::InitVertex(j)), Vertex::Node(VertexType::FromVertex(i))) => {
+                Cost::distance(
+                    metric.distance(requests[i].pos(), &self.instance.initial_positions()[j])
+                        as f64,
+                )
+            }
+            (Vertex::Node(VertexType::FromVertex(_)), Vertex::Node(VertexType::ToVertex(_))) => {
+                Cost::cover()
+            }
+            (Vertex::Node(VertexType::ToVertex(h)), Vertex::Node(VertexType::FromVertex(i))) => {
+                Cost::distance(metric.distance(requests[i].pos(), requests[h].end()) as f64)
+            }
+            _ => Cost::default(),
+        }
+    }
+
+    fn has_flow(&self, u: usize, v: usize) -> bool {
+        if u == SOURCE {
+            self.prev[v] == Some(SOURCE)
+        } else {
+            self.next[u] == Some(v)
+        }
+    }
+
+    /// All edges `(v, is_forward)` of the residual network leaving `u`.
+    fn residual_edges(&self, u: usize) -> Vec<(usize, bool)> {
+        let k = self.instance.k();
+        let n = self.instance.length();
+        let mut forward: Vec<usize> = vec![];
+        let mut backward: Vec<usize> = vec![];
+        match self.vertex(u) {
+            Vertex::Source => forward.extend((0..k).map(|j| 2 + j)),
+            Vertex::Sink => {
+                backward.extend((2..self.next.len()).filter(|&v| self.next[v] == Some(SINK)))
+            }
+            Vertex::Node(VertexType::InitVertex(_)) => {
+                forward.push(SINK);
+                forward.extend((0..n).map(|i| self.index(Vertex::Node(VertexType::FromVertex(i)))));
+                backward.extend(self.prev[u]);
+            }
+            Vertex::Node(VertexType::FromVertex(i)) => {
+                forward.push(self.index(Vertex::Node(VertexType::ToVertex(i))));
+                backward.extend(self.prev[u]);
+            }
+            Vertex::Node(VertexType::ToVertex(i)) => {
+                forward.push(SINK);
+                forward.extend(
+                    (i + 1..n).map(|h| self.index(Vertex::Node(VertexType::FromVertex(h)))),
+                );
+                backward.extend(self.prev[u]);
+            }
+        }
+        forward
+            .into_iter()
+            .filter(|&v| !self.has_flow(u, v))
+            .map(|v| (v, true))
+            .chain(backward.into_iter().map(|v| (v, false)))
+            .collect()
+    }
+
+    /// Sends one unit of flow along a shortest path from source to sink.
+    ///
+    /// Returns `false` if the sink is not reachable anymore.
+    fn augment(&mut self) -> bool {
+        let number_of_vertices = self.next.len();
+        let mut dist: Vec<Option<Cost>> = vec![None; number_of_vertices];
+        let mut parent: Vec<Option<(usize, bool)>> = vec![None; number_of_vertices];
+        let mut done = vec![false; number_of_vertices];
+        dist[SOURCE] = Some(Cost::default());
+
+        // Dijkstra on reduced costs; the network is dense, so we do not use a heap.
+        while let Some(u) = (0..number_of_vertices)
+            .filter(|&v| !done[v] && dist[v].is_some())
+            .min_by(|&a, &b| dist[a].partial_cmp(&dist[b]).unwrap())
+        {
+            done[u] = true;
+            let du = dist[u].unwrap();
+            for (v, is_forward) in self.residual_edges(u) {
+                if done[v] {
+                    continue;
+                }
+                let cost = if is_forward {
+                    self.edge_cost(u, v)
+                } else {
+                    -self.edge_cost(v, u)
+                };
+                let dv = du + cost + self.potentials[u] - self.potentials[v];
+                if dist[v].is_none_or(|d| dv < d) {
+                    dist[v] = Some(dv);
+                    parent[v] = Some((u, is_forward));
+                }
+            }
+        }
+
+        if dist[SINK].is_none() {
+            return false;
+        }
+        for (v, d) in dist.iter().enumerate() {
+            if let Some(d) = d {
+                self.potentials[v] = self.potentials[v] + *d;
+            }
+        }
+
+        let mut path = vec![];
+        let mut v = SINK;
+        while let Some((u, is_forward)) = parent[v] {
+            path.push((u, v, is_forward));
+            v = u;
+        }
+        // Cancel flow first, since a vertex may get a new predecessor on the same path.
+        for &(u, v, is_forward) in path.iter() {
+            if !is_forward {
+                self.next[v] = None;
+                self.prev[u] = None;
+            }
+        }
+        for &(u, v, is_forward) in path.iter() {
+            if is_forward {
+                if u != SOURCE {
+                    self.next[u] = Some(v);
+                }
+                if v != SINK {
+                    self.prev[v] = Some(u);
+                }
+            }
+        }
+        true
+    }
+
+    fn uncovered_requests(&self) -> usize {
+        (0..self.instance.length())
+            .filter(|&i| {
+                let from = self.index(Vertex::Node(VertexType::FromVertex(i)));
+                let to = self.index(Vertex::Node(VertexType::ToVertex(i)));
+                !self.has_flow(from, to)
+            })
+            .count()
+    }
+
+    /// Decomposes the flow into `(server, request)` tuples and returns them with the total cost.
+    fn paths(&self) -> (Vec<(usize, usize)>, f64) {
+        let mut tuples = vec![];
+        let mut costs = 0.0;
+        for j in 0..self.instance.k() {
+            let mut u = self.index(Vertex::Node(VertexType::InitVertex(j)));
+            while let Some(v) = self.next[u] {
+                costs += self.edge_cost(u, v).distance;
+                if let Vertex::Node(VertexType::FromVertex(i)) = self.vertex(v) {
+                    tuples.push((j, i));
+                }
+                u = v;
+            }
+        }
+        (tuples, costs)
+    }
+}
+
+fn create_schedule(tuples: Vec<(usize, usize)>, instance: &Instance) -> Schedule {
     let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
     // server index to request index
-    let tuples: Vec<(usize, usize)> = paths
-        .iter()
-        .enumerate()
-        .flat_map(|(index, path)| {
-            path.flows
-                .iter()
-                .filter_map(|flow| is_move_edge(&flow.a, &flow.b))
-                .map(move |request| (index, request))
-        })
-        .collect();
-
     //println!("{:?}", tuples);
     let number_of_requests = instance.length();
     let mut req_to_server: HashMap<usize, usize> = tuples
@@ -210,6 +577,26 @@ fn create_schedule(paths: Vec<mcmf::Path<VertexType>>, instance: &Instance) -> S
     schedule
 }
 
+/// Creates a schedule by following the servers of the flow, which is optimal in every metric space.
+///
+/// Unlike `create_schedule`, servers are never exchanged.
+fn create_metric_schedule<M: Metric>(
+    tuples: Vec<(usize, usize)>,
+    instance: &Instance<M>,
+) -> Schedule<M::Point> {
+    let mut schedule = Schedule::with_initial_config(instance.initial_positions().clone());
+    let mut positions: Vec<M::Point> = instance.initial_positions().into_iter().copied().collect();
+    let req_to_server: HashMap<usize, usize> = tuples
+        .into_iter()
+        .map(|(server, request)| (request, server))
+        .collect();
+    for (req_index, req) in instance.requests().iter().enumerate() {
+        positions[req_to_server[&req_index]] = *req.end();
+        schedule.append_config(ServerConfiguration::new(positions.clone()));
+    }
+    schedule
+}
+
 fn switch_server_indices(mapping: &mut HashMap<usize, usize>, length: usize, i: usize, j: usize) {
     for req_index in 0..length {
         if mapping.get(&req_index) == Some(&i) {
@@ -229,7 +616,18 @@ mod tests {
         let instance = Instance::from((vec![78, 77, 30, 8, 15, 58, 37, 19, 11, 7], vec![91, 91]));
         let solution = instance.solve();
         let (_, costs) = solution?;
-        assert_eq!(160, costs);
+        assert_eq!(160.0, costs);
+        Ok(())
+    }
+
+    #[test]
+    fn solver_charges_relocations_in_hard_model() -> Result<(), Box<dyn Error>> {
+        let instance = Instance::from((vec![(10, 30), (25, 0)], vec![0, 20]));
+        let (schedule, easy) = instance.solve_with_model(CostModel::Easy)?;
+        let (_, hard) = instance.solve_with_model(CostModel::Hard)?;
+        assert_eq!(15.0, easy);
+        assert_eq!(15.0 + 20.0 + 25.0, hard);
+        assert_eq!(hard, schedule.taxi_cost(&instance, CostModel::Hard));
         Ok(())
     }
 
@@ -264,4 +662,91 @@ mod tests {
         assert_eq!(solution, instance.solve()?.0);
         Ok(())
     }
+
+    #[test]
+    fn solver_fractional_costs_works() -> Result<(), Box<dyn Error>> {
+        let instance = Instance::from((vec![0.5, 2.25, 0.75], vec![1.0, 1.0]));
+        let (schedule, costs) = instance.solve()?;
+        assert_eq!(2.0, costs);
+        assert_eq!(2.0, schedule.cost());
+        Ok(())
+    }
+
+    #[test]
+    fn solver_negative_positions_works() -> Result<(), Box<dyn Error>> {
+        let instance = Instance::from((vec![(-10, -20), (5, 5), (-20, -20)], vec![0, 0]));
+        let (_, costs) = instance.solve()?;
+        assert_eq!(15.0, costs);
+        Ok(())
+    }
+
+    #[test]
+    fn solver_without_servers_fails() {
+        let instance = Instance::from((vec![1, 2, 3], vec![]));
+        assert!(instance.solve().is_err());
+    }
+
+    #[test]
+    fn circle_solver_works() -> Result<(), Box<dyn Error>> {
+        let circle = Circle::new(100.0);
+        let instance = Instance::with_metric(
+            circle,
+            vec![95.0, 5.0, 50.0]
+                .into_iter()
+                .map(|x| Request::Simple(circle.point(x)))
+                .collect(),
+            ServerConfiguration::new(vec![circle.point(10.0), circle.point(40.0)]),
+        );
+        let (schedule, costs) = instance.solve_metric()?;
+        assert_eq!(35.0, costs);
+        assert_eq!(35.0, schedule.metric_cost(&circle));
+        Ok(())
+    }
+
+    #[test]
+    fn tree_solver_equals_line_solver_on_path() -> Result<(), Box<dyn Error>> {
+        let requests = vec![78, 77, 30, 8, 15, 58, 37, 19, 11, 7];
+        let edges: Vec<(usize, usize, f32)> = (0..100).map(|i| (i, i + 1, 1.0)).collect();
+        let tree = Tree::from_edges(101, &edges)?;
+        let instance = Instance::with_metric(
+            tree.clone(),
+            requests
+                .iter()
+                .map(|&r| Request::Simple(TreePoint::from(r)))
+                .collect(),
+            ServerConfiguration::new(vec![TreePoint::from(91); 2]),
+        );
+        let (schedule, costs) = instance.solve_metric()?;
+        assert_eq!(160.0, costs);
+        assert_eq!(160.0, schedule.metric_cost(&tree));
+        Ok(())
+    }
+
+    #[test]
+    fn plane_solver_works() -> Result<(), Box<dyn Error>> {
+        let instance = Instance::with_metric(
+            Plane,
+            vec![(3, 4), (6, 4), (0, 1)]
+                .into_iter()
+                .map(|p| Request::Simple(PlanePoint::from(p)))
+                .collect(),
+            ServerConfiguration::new(vec![PlanePoint::from((0, 0)), PlanePoint::from((6, 0))]),
+        );
+        let (schedule, costs) = instance.solve_metric()?;
+        assert_eq!(5.0 + 3.0 + 1.0, costs);
+        assert_eq!(
+            &ServerConfiguration::new(vec![PlanePoint::from((0, 1)), PlanePoint::from((6, 4))]),
+            schedule.last().unwrap()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn metric_solver_equals_solver_on_line() -> Result<(), Box<dyn Error>> {
+        let instance = Instance::from((vec![17, 17, 5, 14, 16, 17], vec![14, 14]));
+        let (schedule, costs) = instance.solve_metric()?;
+        assert_eq!(instance.solve()?.1, costs);
+        assert_eq!(costs, schedule.metric_cost(&Line));
+        Ok(())
+    }
 }
@@ -0,0 +1,58 @@
+use serverlib::prelude::*;
+
+use crate::solver::{MetricSolver, SolverError};
+use ktaxi::checker::check_schedule;
+
+/// Computes an optimal solution of a k-taxi instance on the line.
+///
+/// The flow network charges the distance from the end of a request to the start of the next request
+/// served by the same server, i.e., the easy k-taxi cost. In contrast to `Solver`, which exchanges
+/// adjacent servers at the start of a request, servers are followed along their paths in the flow,
+/// so relocations are never exchanged between servers. The solution is verified by `check_schedule`.
+pub trait TaxiSolver {
+    fn solve_taxi(&self, model: CostModel) -> Result<(Schedule, f64), SolverError>;
+}
+
+impl TaxiSolver for Instance {
+    fn solve_taxi(&self, model: CostModel) -> Result<(Schedule, f64), SolverError> {
+        let (mut schedule, costs) = self.solve_metric()?;
+        schedule.normalize();
+        let costs = costs + model.relocation_costs(self);
+        check_schedule(self, &schedule, costs, model)
+            .map_err(|e| SolverError::new(format!("Inconsistent OPT: {}", e)))?;
+        Ok((schedule, costs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dp_solver::DPSolver;
+
+    #[test]
+    fn taxi_solver_equals_dp() -> Result<(), SolverError> {
+        let instance = Instance::from((
+            vec![
+                (78, 3),
+                (77, 30),
+                (8, 15),
+                (58, 37),
+                (19, 11),
+                (7, 91),
+                (50, 50),
+            ],
+            vec![91, 10, 40],
+        ));
+        let (schedule, costs) = instance.solve_taxi(CostModel::Easy)?;
+        let (_, dp_costs) = instance.solve_dp()?;
+        assert_eq!(dp_costs, costs);
+        assert_eq!(costs, schedule.taxi_cost(&instance, CostModel::Easy));
+
+        let (_, hard_costs) = instance.solve_taxi(CostModel::Hard)?;
+        assert_eq!(
+            costs + CostModel::Hard.relocation_costs(&instance),
+            hard_costs
+        );
+        Ok(())
+    }
+}
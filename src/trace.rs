@@ -0,0 +1,224 @@
+use kserver::algorithms::*;
+use samplelib::*;
+use serverlib::prelude::*;
+
+use console::style;
+use csv::WriterBuilder;
+use serde::Serialize;
+use std::error::Error;
+use std::{fmt, fs};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct TraceConfig {
+    /// The instance file to trace
+    pub file: String,
+
+    /// Index of the traced prediction, where 0 is the perfect prediction
+    #[structopt(long, default_value = "0")]
+    pub prediction: usize,
+
+    #[structopt(long, default_value = "0.5")]
+    pub lambda: f32,
+
+    #[structopt(long, default_value = "1.0")]
+    pub gamma: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceError {
+    msg: String,
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl Error for TraceError {
+    fn description(&self) -> &str {
+        &self.msg
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        None
+    }
+}
+
+impl TraceError {
+    pub(crate) fn new(msg: String) -> TraceError {
+        TraceError { msg }
+    }
+}
+
+/// The state of one algorithm after serving a request.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AlgorithmStep {
+    pub name: String,
+    pub config: Vec<f32>,
+    pub cost: f64,
+    pub total: f64,
+}
+
+/// A single request of the traced instance together with the reaction of each algorithm.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TraceRow {
+    pub step: usize,
+    pub request: String,
+    pub predicted: usize,
+    pub opt: Vec<f32>,
+    pub algorithms: Vec<AlgorithmStep>,
+}
+
+/// Simulates the k-server algorithms with the given prediction and records every step.
+pub fn trace(sample: &Sample, prediction: &Prediction, lambda: f32, gamma: f64) -> Vec<TraceRow> {
+    let instance = &sample.instance;
+    let schedules: Vec<(&str, Schedule)> = vec![
+        ("DC", deterministic_alg(DoubleCoverage, instance).0),
+        (
+            "LDC",
+            learning_augmented_alg(LambdaDC::new(lambda), instance, prediction).0,
+        ),
+        (
+            "RobustFtp",
+            learning_augmented_alg(CombineDet::new(gamma), instance, prediction).0,
+        ),
+    ];
+
+    let mut totals = vec![0.0; schedules.len()];
+    instance
+        .requests()
+        .iter()
+        .enumerate()
+        .map(|(idx, req)| {
+            let algorithms = schedules
+                .iter()
+                .zip(totals.iter_mut())
+                .map(|((name, schedule), total)| {
+                    let cost = instance
+                        .metric()
+                        .config_distance(&schedule[idx], &schedule[idx + 1]);
+                    *total += cost;
+                    AlgorithmStep {
+                        name: name.to_string(),
+                        config: positions(&schedule[idx + 1]),
+                        cost,
+                        total: *total,
+                    }
+                })
+                .collect();
+            TraceRow {
+                step: idx + 1,
+                request: req.to_string(),
+                predicted: prediction[idx],
+                opt: positions(&sample.solution[idx + 1]),
+                algorithms,
+            }
+        })
+        .collect()
+}
+
+fn positions(config: &ServerConfiguration) -> Vec<f32> {
+    config.into_iter().cloned().collect()
+}
+
+/// The prediction with the given index, where 0 is the perfect prediction.
+pub(crate) fn select_prediction(sample: &Sample, index: usize) -> Result<&Prediction, TraceError> {
+    sample.predictions.get(index).ok_or_else(|| {
+        TraceError::new(format!(
+            "There is no prediction {}, only {} have been generated",
+            index,
+            sample.predictions.len()
+        ))
+    })
+}
+
+/// Traces the sample and writes the rows to `output`, as JSON if it ends with `.json` and as CSV otherwise.
+pub fn run(sample: &Sample, config: &TraceConfig, output: &str) -> Result<(), Box<dyn Error>> {
+    let prediction = select_prediction(sample, config.prediction)?;
+    println!(
+        "{}",
+        style(format!("Start tracing to {}...", output))
+            .bold()
+            .cyan()
+    );
+    let rows = trace(sample, prediction, config.lambda, config.gamma);
+    if output.ends_with(".json") {
+        fs::write(output, serde_json::to_string_pretty(&rows)?)?;
+    } else {
+        write_csv(&rows, output)?;
+    }
+    println!("{}", style("Tracing finished!").bold().green());
+
+    Ok(())
+}
+
+fn write_csv(rows: &[TraceRow], output: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = WriterBuilder::new().has_headers(false).from_path(output)?;
+    let join = |config: &[f32]| {
+        config
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+
+    if let Some(first) = rows.first() {
+        let mut headers: Vec<String> = vec!["Step", "Request", "Predicted", "OptConfig"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        for alg in &first.algorithms {
+            headers.push(format!("{}Config", alg.name));
+            headers.push(format!("{}Cost", alg.name));
+            headers.push(format!("{}Total", alg.name));
+        }
+        wtr.write_record(headers)?;
+    }
+    for row in rows {
+        let mut record = vec![
+            row.step.to_string(),
+            row.request.clone(),
+            row.predicted.to_string(),
+            join(&row.opt),
+        ];
+        for alg in &row.algorithms {
+            record.push(join(&alg.config));
+            record.push(alg.cost.to_string());
+            record.push(alg.total.to_string());
+        }
+        wtr.write_record(record)?;
+    }
+    wtr.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trace_works() {
+        let instance = Instance::from((vec![20, 80, 30], vec![50, 50]));
+        let solution = Schedule::from(vec![vec![50, 50], vec![20, 50], vec![20, 80], vec![30, 80]]);
+        let sample = Sample::new(instance, solution, 70.0);
+        let prediction = Prediction::from(vec![0, 1, 0]);
+        let rows = trace(&sample, &prediction, 0.0, 1.0);
+
+        assert_eq!(3, rows.len());
+        assert_eq!("80", rows[1].request);
+        assert_eq!(1, rows[1].predicted);
+        assert_eq!(vec![20.0, 80.0], rows[1].opt);
+        // with lambda = 0 and a perfect prediction, LambdaDC follows OPT
+        let ldc: Vec<&AlgorithmStep> = rows.iter().map(|row| &row.algorithms[1]).collect();
+        assert_eq!(vec![30.0, 80.0], ldc[2].config);
+        assert_eq!(30.0 + 30.0 + 10.0, ldc[2].total);
+        for row in &rows {
+            let dc = &row.algorithms[0];
+            assert_eq!("DC", dc.name);
+            assert!(dc.cost <= dc.total);
+        }
+    }
+}
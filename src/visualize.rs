@@ -0,0 +1,58 @@
+use crate::sim::parse_algorithm_name;
+use crate::trace::select_prediction;
+use kserver::algorithms::algorithm_from_name;
+use samplelib::*;
+use serverlib::svg::render_svg;
+use serverlib::Error as ServerError;
+
+use console::style;
+use std::error::Error;
+use std::fs;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct VisualizeConfig {
+    /// The instance file to visualize
+    pub file: String,
+
+    /// Index of the highlighted prediction, where 0 is the perfect prediction
+    #[structopt(long, default_value = "0")]
+    pub prediction: usize,
+
+    #[structopt(long, default_value = "0.5")]
+    pub lambda: f32,
+
+    /// The algorithms drawn next to OPT, e.g. "dc,ldc"
+    #[structopt(long, default_value = "dc,ldc", use_delimiter = true, parse(try_from_str = parse_algorithm_name))]
+    pub algorithms: Vec<String>,
+
+    #[structopt(long = "svg", default_value = "schedules.svg")]
+    pub output_file: String,
+}
+
+/// Draws OPT and the configured algorithms on the sample as an SVG space-time diagram.
+pub fn run(sample: &Sample, config: &VisualizeConfig) -> Result<(), Box<dyn Error>> {
+    let prediction = select_prediction(sample, config.prediction)?;
+    println!(
+        "{}",
+        style(format!("Start drawing to {}...", config.output_file))
+            .bold()
+            .cyan()
+    );
+    let schedules = config
+        .algorithms
+        .iter()
+        .map(|name| {
+            let alg = algorithm_from_name(name, config.lambda)?;
+            Ok((name.as_str(), alg.try_run(&sample.instance, prediction)?.0))
+        })
+        .collect::<Result<Vec<_>, ServerError>>()?;
+    let mut drawn = vec![("OPT", &sample.solution)];
+    drawn.extend(schedules.iter().map(|(name, schedule)| (*name, schedule)));
+
+    let svg = render_svg(&sample.instance, &drawn, Some(prediction))?;
+    fs::write(&config.output_file, svg)?;
+    println!("{}", style("Drawing finished!").bold().green());
+
+    Ok(())
+}